@@ -3,7 +3,7 @@ extern crate test;
 use test::Bencher;
 
 use nicod::expr::*;
-use nicod::lang::RuleSet;
+use nicod::lang::{RuleSet, SearchStrategy};
 use nicod::*;
 
 fn transitive_rules() -> RuleSet {
@@ -39,34 +39,100 @@ fn transitive_rules() -> RuleSet {
     rules
 }
 
-fn subtype_search(bench: &mut Bencher, typ: Expr) {
+fn subtype_search(bench: &mut Bencher, strategy: SearchStrategy, typ: Expr) {
     let rules = transitive_rules();
     let query = expr!(seq(wrd(a), wrd(sub), var(t)));
     let target = expr!(var(t));
 
     bench.iter(|| {
         rules
-            .derive(&query)
+            .derive_with(&query, strategy)
             .find(|(s, _)| s.resolve(&target) == typ);
     });
 }
 
 #[bench]
 fn breadth_first_b(bench: &mut Bencher) {
-    subtype_search(bench, expr!(wrd(b)));
+    subtype_search(bench, SearchStrategy::Interleave, expr!(wrd(b)));
 }
 
 #[bench]
 fn breadth_first_c(bench: &mut Bencher) {
-    subtype_search(bench, expr!(wrd(c)));
+    subtype_search(bench, SearchStrategy::Interleave, expr!(wrd(c)));
 }
 
 #[bench]
 fn breadth_first_d(bench: &mut Bencher) {
-    subtype_search(bench, expr!(wrd(d)));
+    subtype_search(bench, SearchStrategy::Interleave, expr!(wrd(d)));
 }
 
 #[bench]
 fn breadth_first_e(bench: &mut Bencher) {
-    subtype_search(bench, expr!(wrd(e)));
+    subtype_search(bench, SearchStrategy::Interleave, expr!(wrd(e)));
+}
+
+#[bench]
+fn iterative_deepening_b(bench: &mut Bencher) {
+    subtype_search(bench, SearchStrategy::IterativeDeepening, expr!(wrd(b)));
+}
+
+#[bench]
+fn iterative_deepening_c(bench: &mut Bencher) {
+    subtype_search(bench, SearchStrategy::IterativeDeepening, expr!(wrd(c)));
+}
+
+#[bench]
+fn iterative_deepening_d(bench: &mut Bencher) {
+    subtype_search(bench, SearchStrategy::IterativeDeepening, expr!(wrd(d)));
+}
+
+#[bench]
+fn iterative_deepening_e(bench: &mut Bencher) {
+    subtype_search(bench, SearchStrategy::IterativeDeepening, expr!(wrd(e)));
+}
+
+#[bench]
+fn tabled_closure(bench: &mut Bencher) {
+    let mut rules = transitive_rules();
+    rules.table(&expr!(seq(var(x), wrd(sub), var(y))));
+
+    let query = expr!(seq(wrd(a), wrd(sub), var(t)));
+
+    bench.iter(|| rules.derive(&query).count());
+}
+
+fn construct_rules(indexing: bool) -> RuleSet {
+    let mut rules = RuleSet::new();
+    rules.set_indexing(indexing);
+
+    rules.load_str("rule eval-a\n    eval a = a\n").unwrap();
+
+    for i in 0..200 {
+        let source = format!(
+            "rule eval-{0}\n    eval (op{0} $x) = $v :- eval $x = $v\n",
+            i
+        );
+        rules.load_str(&source).unwrap();
+    }
+
+    rules
+}
+
+fn construct_search(bench: &mut Bencher, indexing: bool) {
+    let rules = construct_rules(indexing);
+    let query: Expr = "eval (op199 (op150 (op100 (op50 (op0 a))))) = $v"
+        .parse()
+        .unwrap();
+
+    bench.iter(|| rules.derive(&query).count());
+}
+
+#[bench]
+fn many_rules_linear(bench: &mut Bencher) {
+    construct_search(bench, false);
+}
+
+#[bench]
+fn many_rules_indexed(bench: &mut Bencher) {
+    construct_search(bench, true);
 }
@@ -5,10 +5,10 @@ use nicod::*;
 fn derive(rules: &RuleSet, query: Expr) {
     println!("----[ {} ]----", query);
 
-    for (i, (state, _)) in rules.derive(&query).enumerate() {
-        println!("#{}: {}", i + 1, state.resolve(&query));
+    for (i, solution) in rules.query(&query).enumerate() {
+        println!("#{}: {}", i + 1, solution);
     }
-    println!("");
+    println!();
 }
 
 fn main() {
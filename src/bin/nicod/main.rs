@@ -0,0 +1,75 @@
+mod options;
+mod repl;
+mod tests;
+
+use nicod::expr::{parse, Expr};
+use nicod::lang::RuleSet;
+use nicod::proof::RenderOptions;
+use options::{Options, USAGE};
+use repl::Repl;
+use std::env;
+use std::io;
+use std::process;
+
+fn main() {
+    let options = Options::parse(env::args().skip(1)).unwrap_or_else(|message| {
+        eprintln!("nicod: {}\n\n{}", message, USAGE);
+        process::exit(2);
+    });
+
+    if options.help {
+        print!("{}", USAGE);
+        return;
+    }
+
+    let mut rules = RuleSet::new();
+
+    for path in &options.files {
+        if let Err(err) = rules.load_file(path) {
+            fail(err);
+        }
+    }
+
+    let query = match &options.query {
+        Some(query) => query,
+        None => {
+            let stdin = io::stdin();
+            let mut repl = Repl::new(stdin.lock(), io::stdout());
+            if let Err(err) = repl.run(&mut rules) {
+                fail(err);
+            }
+            return;
+        }
+    };
+
+    let query = parse(query).unwrap_or_else(|err| fail(format!("query:{}", err)));
+
+    if run(&rules, &query, &options) == 0 {
+        println!("no solutions");
+        process::exit(1);
+    }
+}
+
+fn run(rules: &RuleSet, query: &Expr, options: &Options) -> usize {
+    let limit = options.limit.unwrap_or(usize::MAX);
+    let mut count = 0;
+
+    for (state, proof) in rules.derive(query).take(limit) {
+        count += 1;
+        println!("#{}: {}", count, state.reify(query));
+
+        if options.proof {
+            let render = RenderOptions {
+                max_width: options.width,
+            };
+            println!("\n{}", proof.render_with(&render));
+        }
+    }
+
+    count
+}
+
+fn fail<E: ToString>(err: E) -> ! {
+    eprintln!("nicod: {}", err.to_string());
+    process::exit(2);
+}
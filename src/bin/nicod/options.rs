@@ -0,0 +1,71 @@
+pub const USAGE: &str = "\
+Usage: nicod [OPTIONS] [FILE]...
+
+Loads rules from each FILE and prints the solutions to QUERY. Without a
+query, starts an interactive session; enter :help there for its commands.
+
+Options:
+    -q, --query <QUERY>    Expression to derive from the rules
+    -n, --limit <N>        Stop after printing N solutions (N > 0)
+    -p, --proof            Print the proof tree of each solution
+    -w, --width <N>        Split proof trees wider than N columns
+    -h, --help             Print this message
+";
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Options {
+    pub files: Vec<String>,
+    pub query: Option<String>,
+    pub limit: Option<usize>,
+    pub proof: bool,
+    pub width: Option<usize>,
+    pub help: bool,
+}
+
+impl Options {
+    pub fn parse<I, S>(args: I) -> Result<Options, String>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut options = Options::default();
+        let mut args = args.into_iter().map(Into::into);
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-q" | "--query" => options.query = Some(value(&arg, args.next())?),
+                "-n" | "--limit" => {
+                    let limit = value(&arg, args.next())?;
+                    let limit = match limit.parse() {
+                        Ok(n) if n > 0 => n,
+                        _ => return Err(format!("invalid limit '{}'", limit)),
+                    };
+                    options.limit = Some(limit);
+                }
+                "-p" | "--proof" => options.proof = true,
+                "-w" | "--width" => {
+                    let width = value(&arg, args.next())?;
+                    let width = width
+                        .parse()
+                        .map_err(|_| format!("invalid width '{}'", width))?;
+                    options.width = Some(width);
+                }
+                "-h" | "--help" => options.help = true,
+                _ if arg.starts_with('-') && arg.len() > 1 => {
+                    return Err(format!("unknown option '{}'", arg));
+                }
+                _ => options.files.push(arg),
+            }
+        }
+
+        if options.query.is_some() && options.files.is_empty() {
+            return Err(String::from("no rule files given"));
+        }
+
+        Ok(options)
+    }
+}
+
+fn value(option: &str, arg: Option<String>) -> Result<String, String> {
+    arg.ok_or_else(|| format!("option '{}' requires a value", option))
+}
@@ -0,0 +1,252 @@
+use nicod::expr::{parse, Expr, ParseError};
+use nicod::lang::RuleSet;
+use nicod::proof::Proof;
+use nicod::state::State;
+use std::io::{self, BufRead, Write};
+use std::rc::Rc;
+
+const PROMPT: &str = "?- ";
+
+const HELP: &str = "\
+Enter an expression to query the rules, then:
+
+    <enter>, :next       Show the next solution
+    :all                 Show all remaining solutions
+    :proof               Show the proof of the last solution
+
+Other commands:
+
+    :rule <name> <rule>  Add a rule, e.g. `:rule S-Refl $x sub $x`
+                         or `:rule S-Trans $x sub $z :- $x sub $y, $y sub $z`
+    :load <file>         Load rules from a file
+    :rules               List all rules
+    :history             List previous inputs; `!<n>` repeats input <n>
+    :help                Show this message
+    :quit                Exit
+";
+
+enum Command {
+    Query(Expr),
+    Next,
+    All,
+    Proof,
+    Rule(String, String, usize),
+    Load(String),
+    Rules,
+    History,
+    Help,
+    Quit,
+}
+
+impl Command {
+    fn parse(line: &str) -> Result<Command, String> {
+        let (word, arg) = match line.find(char::is_whitespace) {
+            Some(offset) => (&line[..offset], line[offset..].trim()),
+            None => (line, ""),
+        };
+
+        let command = match word {
+            "" | ":next" => Command::Next,
+            ":all" => Command::All,
+            ":proof" => Command::Proof,
+            ":rule" => match arg.find(char::is_whitespace) {
+                Some(offset) => {
+                    let body = arg[offset..].trim_start();
+                    let column = line.chars().count() - body.chars().count();
+                    Command::Rule(String::from(&arg[..offset]), String::from(body), column)
+                }
+                None => return Err(String::from("usage: :rule <name> <rule>")),
+            },
+            ":load" if !arg.is_empty() => Command::Load(String::from(arg)),
+            ":load" => return Err(String::from("usage: :load <file>")),
+            ":rules" => Command::Rules,
+            ":history" => Command::History,
+            ":help" => Command::Help,
+            ":quit" => Command::Quit,
+            _ if word.starts_with(':') => return Err(format!("unknown command '{}'", word)),
+            _ => match parse(line) {
+                Ok(query) => Command::Query(query),
+                Err(err) => return Err(describe(err, 0)),
+            },
+        };
+
+        Ok(command)
+    }
+}
+
+enum Edit {
+    Rule(String, String, usize),
+    Load(String),
+}
+
+struct Query<'a> {
+    expr: Expr,
+    answers: Box<dyn Iterator<Item = (State, Rc<Proof>)> + 'a>,
+    count: usize,
+    proof: Option<Rc<Proof>>,
+}
+
+impl<'a> Query<'a> {
+    fn new(rules: &'a RuleSet, expr: Expr) -> Query<'a> {
+        let answers = rules.derive(&expr);
+
+        Query {
+            expr,
+            answers,
+            count: 0,
+            proof: None,
+        }
+    }
+
+    fn next(&mut self, output: &mut impl Write) -> io::Result<bool> {
+        if let Some((state, proof)) = self.answers.next() {
+            self.count += 1;
+            self.proof = Some(proof);
+            writeln!(output, "#{}: {}", self.count, state.reify(&self.expr))?;
+            Ok(true)
+        } else {
+            if self.count == 0 {
+                writeln!(output, "no solutions")?;
+            } else {
+                writeln!(output, "no more solutions")?;
+            }
+            Ok(false)
+        }
+    }
+}
+
+pub struct Repl<R, W> {
+    input: R,
+    output: W,
+    history: Vec<String>,
+}
+
+impl<R: BufRead, W: Write> Repl<R, W> {
+    pub fn new(input: R, output: W) -> Repl<R, W> {
+        Repl {
+            input,
+            output,
+            history: Vec::new(),
+        }
+    }
+
+    pub fn run(&mut self, rules: &mut RuleSet) -> io::Result<()> {
+        while let Some(edit) = self.session(rules)? {
+            match edit {
+                Edit::Rule(name, body, column) => {
+                    let source = format!("rule {}\n{}", name, body);
+
+                    match rules.load_str(&source) {
+                        Ok(()) => writeln!(self.output, "added rule '{}'", name)?,
+                        Err(err) => writeln!(self.output, "error: {}", describe(err, column))?,
+                    }
+                }
+                Edit::Load(path) => match rules.load_file(&path) {
+                    Ok(()) => writeln!(self.output, "loaded '{}'", path)?,
+                    Err(err) => writeln!(self.output, "error: {}", err)?,
+                },
+            }
+        }
+        Ok(())
+    }
+
+    fn session(&mut self, rules: &RuleSet) -> io::Result<Option<Edit>> {
+        let mut query: Option<Query> = None;
+
+        while let Some(line) = self.read_line()? {
+            let line = match self.expand(&line) {
+                Some(expanded) if line.starts_with('!') => {
+                    writeln!(self.output, "{}", expanded)?;
+                    expanded
+                }
+                Some(expanded) => expanded,
+                None => {
+                    writeln!(self.output, "error: no history entry '{}'", &line[1..])?;
+                    continue;
+                }
+            };
+
+            let command = match Command::parse(&line) {
+                Ok(command) => command,
+                Err(message) => {
+                    writeln!(self.output, "error: {}", message)?;
+                    continue;
+                }
+            };
+
+            match (command, &mut query) {
+                (Command::Query(expr), _) => {
+                    let mut next = Query::new(rules, expr);
+                    next.next(&mut self.output)?;
+                    query = Some(next);
+                }
+                (Command::Next, Some(query)) => {
+                    query.next(&mut self.output)?;
+                }
+                (Command::All, Some(query)) => while query.next(&mut self.output)? {},
+                (
+                    Command::Proof,
+                    Some(Query {
+                        proof: Some(proof), ..
+                    }),
+                ) => {
+                    write!(self.output, "{}", proof)?;
+                }
+                (Command::Next, None) | (Command::All, None) => {
+                    if !line.is_empty() {
+                        writeln!(self.output, "error: no query to continue")?;
+                    }
+                }
+                (Command::Proof, _) => writeln!(self.output, "error: no solution to show")?,
+                (Command::Rule(name, body, column), _) => {
+                    return Ok(Some(Edit::Rule(name, body, column)));
+                }
+                (Command::Load(path), _) => return Ok(Some(Edit::Load(path))),
+                (Command::Rules, _) => write!(self.output, "{}", rules)?,
+                (Command::History, _) => {
+                    for (i, line) in self.history.iter().enumerate() {
+                        writeln!(self.output, "{:>4}  {}", i + 1, line)?;
+                    }
+                }
+                (Command::Help, _) => write!(self.output, "{}", HELP)?,
+                (Command::Quit, _) => return Ok(None),
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn read_line(&mut self) -> io::Result<Option<String>> {
+        write!(self.output, "{}", PROMPT)?;
+        self.output.flush()?;
+
+        let mut line = String::new();
+
+        if self.input.read_line(&mut line)? == 0 {
+            writeln!(self.output)?;
+            Ok(None)
+        } else {
+            Ok(Some(String::from(line.trim())))
+        }
+    }
+
+    fn expand(&mut self, line: &str) -> Option<String> {
+        let line = match line.strip_prefix('!') {
+            Some(number) => {
+                let index = number.parse::<usize>().ok()?.checked_sub(1)?;
+                self.history.get(index)?.clone()
+            }
+            None => String::from(line),
+        };
+
+        if !line.is_empty() {
+            self.history.push(line.clone());
+        }
+
+        Some(line)
+    }
+}
+
+fn describe(err: ParseError, offset: usize) -> String {
+    format!("{} at column {}", err.message, err.column + offset)
+}
@@ -0,0 +1,194 @@
+#![cfg(test)]
+
+use crate::options::Options;
+use crate::repl::Repl;
+use nicod::lang::RuleSet;
+
+fn session(rules: &mut RuleSet, input: &str) -> String {
+    let mut output = Vec::new();
+    Repl::new(input.as_bytes(), &mut output).run(rules).unwrap();
+    String::from_utf8(output).unwrap().replace("?- ", "")
+}
+
+fn append_rules() -> RuleSet {
+    let mut rules = RuleSet::new();
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/examples/append.rules");
+    rules.load_file(path).unwrap();
+    rules
+}
+
+#[test]
+fn parse_files_and_query() {
+    let options = Options::parse(vec!["a.rules", "-q", "$x sub b", "b.rules"]).unwrap();
+
+    assert_eq!(
+        options,
+        Options {
+            files: vec![String::from("a.rules"), String::from("b.rules")],
+            query: Some(String::from("$x sub b")),
+            ..Options::default()
+        }
+    );
+}
+
+#[test]
+fn parse_limit_and_proof_flags() {
+    let options = Options::parse(vec!["--limit", "3", "-p", "--query", "a", "x.rules"]).unwrap();
+
+    assert_eq!(options.limit, Some(3));
+    assert!(options.proof);
+}
+
+#[test]
+fn parse_proof_width() {
+    let options = Options::parse(vec!["-p", "-w", "80", "-q", "a", "x.rules"]).unwrap();
+    assert_eq!(options.width, Some(80));
+
+    let error = Options::parse(vec!["--width", "wide", "-q", "a", "a.rules"]).unwrap_err();
+    assert_eq!(error, "invalid width 'wide'");
+}
+
+#[test]
+fn parse_help_without_query() {
+    let options = Options::parse(vec!["--help"]).unwrap();
+    assert!(options.help);
+}
+
+#[test]
+fn parse_files_without_query() {
+    let options = Options::parse(vec!["a.rules"]).unwrap();
+    assert_eq!(options.query, None);
+}
+
+#[test]
+fn reject_missing_files() {
+    let error = Options::parse(vec!["-q", "a"]).unwrap_err();
+    assert_eq!(error, "no rule files given");
+}
+
+#[test]
+fn reject_missing_option_value() {
+    let error = Options::parse(vec!["a.rules", "-q"]).unwrap_err();
+    assert_eq!(error, "option '-q' requires a value");
+}
+
+#[test]
+fn reject_invalid_limit() {
+    let error = Options::parse(vec!["-n", "lots", "-q", "a", "a.rules"]).unwrap_err();
+    assert_eq!(error, "invalid limit 'lots'");
+}
+
+#[test]
+fn reject_zero_limit() {
+    let error = Options::parse(vec!["-n", "0", "-q", "a", "a.rules"]).unwrap_err();
+    assert_eq!(error, "invalid limit '0'");
+}
+
+#[test]
+fn reject_unknown_options() {
+    let error = Options::parse(vec!["--verbose", "-q", "a", "a.rules"]).unwrap_err();
+    assert_eq!(error, "unknown option '--verbose'");
+}
+
+#[test]
+fn step_through_solutions() {
+    let output = session(&mut append_rules(), "$x + $y = λ[a]\n\n:next\n:next\n");
+
+    assert_eq!(
+        output,
+        "#1: λ[] + λ[a] = λ[a]\n\
+         #2: λ[a] + λ[] = λ[a]\n\
+         no more solutions\n\
+         no more solutions\n\n"
+    );
+}
+
+#[test]
+fn show_all_solutions_and_proof() {
+    let output = session(&mut append_rules(), "λ[a] + λ[b] = $z\n:all\n:proof\n");
+
+    assert_eq!(
+        output,
+        "#1: λ[a] + λ[b] = λ[a, b]\n\
+         no more solutions\n      \
+         ───────────────── append-0\n      \
+         λ[] + λ[b] = λ[b]\n    \
+         ───────────────────── append-N\n    \
+         λ[a] + λ[b] = λ[a, b]\n\n"
+    );
+}
+
+#[test]
+fn add_and_list_rules() {
+    let mut rules = RuleSet::new();
+    let output = session(
+        &mut rules,
+        ":rule ab a sub b\n:rule refl $x sub $x\n:rules\n$x sub b\n:all\n",
+    );
+
+    assert_eq!(
+        output,
+        "added rule 'ab'\n\
+         added rule 'refl'\n\
+         rule ab\n    a sub b\n\n\
+         rule refl\n    $x sub $x\n\
+         #1: a sub b\n\
+         #2: b sub b\n\
+         no more solutions\n\n"
+    );
+}
+
+#[test]
+fn report_errors_with_columns() {
+    let output = session(
+        &mut RuleSet::new(),
+        "f (x\n:rule r a :- (b\n:proof\n:what\n",
+    );
+
+    assert_eq!(
+        output,
+        "error: expected ')', found end of input at column 5\n\
+         error: expected ')', found end of input at column 16\n\
+         error: no solution to show\n\
+         error: unknown command ':what'\n\n"
+    );
+}
+
+#[test]
+fn repeat_inputs_from_history() {
+    let mut rules = RuleSet::new();
+    let output = session(&mut rules, ":rule a x\nx\n:history\n!2\n!9\n");
+
+    assert_eq!(
+        output,
+        "added rule 'a'\n\
+         #1: x\n\
+         \x20  1  :rule a x\n\
+         \x20  2  x\n\
+         \x20  3  :history\n\
+         x\n\
+         #1: x\n\
+         error: no history entry '9'\n\n"
+    );
+}
+
+#[test]
+fn load_rules_from_prompt() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/examples/append.rules");
+    let input = format!(":load {}\nrev λ[a, b] = $r\n:load nowhere\n", path);
+    let output = session(&mut RuleSet::new(), &input);
+
+    assert!(output.starts_with(&format!("loaded '{}'\n#1: rev λ[a, b] = λ[b, a]\n", path)));
+    assert!(output.contains("error: nowhere: "));
+}
+
+#[test]
+fn name_free_variables_in_answers_canonically() {
+    let output = session(&mut append_rules(), "$x + $y = $z\n\n");
+
+    assert_eq!(
+        output,
+        "#1: λ[] + $_0 = $_0\n\
+         #2: λ[$_0] + $_1 = λ[$_0 | $_1]\n\n"
+    );
+}
@@ -0,0 +1,98 @@
+use super::ParseError;
+use std::iter::Peekable;
+use std::str::Chars;
+
+pub(crate) struct Cursor<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn at(input: &'a str, line: usize, column: usize) -> Cursor<'a> {
+        Cursor {
+            chars: input.chars().peekable(),
+            line,
+            column,
+        }
+    }
+
+    pub fn peek(&mut self) -> Option<&char> {
+        self.chars.peek()
+    }
+
+    pub fn location(&self) -> (usize, usize) {
+        (self.line, self.column)
+    }
+
+    pub fn position(&mut self) -> (usize, usize) {
+        self.skip_whitespace();
+        self.location()
+    }
+
+    pub fn skip(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+
+        if self.chars.peek() == Some(&c) {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn expect(&mut self, expected: char) -> Result<(), ParseError> {
+        if self.skip(expected) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("'{}'", expected)))
+        }
+    }
+
+    pub fn exact(&mut self, expected: char) -> Result<(), ParseError> {
+        if self.chars.peek() == Some(&expected) {
+            self.advance();
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("'{}'", expected)))
+        }
+    }
+
+    pub fn is_done(&mut self) -> bool {
+        self.skip_whitespace();
+        self.chars.peek().is_none()
+    }
+
+    pub fn skip_whitespace(&mut self) {
+        while let Some(c) = self.chars.peek() {
+            if !c.is_whitespace() {
+                break;
+            }
+            self.advance();
+        }
+    }
+
+    pub fn advance(&mut self) {
+        if let Some(c) = self.chars.next() {
+            if c == '\n' {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
+        }
+    }
+
+    pub fn unexpected(&mut self, expected: &str) -> ParseError {
+        let found = match self.chars.peek() {
+            Some(c) => format!("'{}'", c),
+            None => String::from("end of input"),
+        };
+
+        ParseError {
+            line: self.line,
+            column: self.column,
+            message: format!("expected {}, found {}", expected, found),
+        }
+    }
+}
@@ -1,10 +1,23 @@
+mod cursor;
+mod ops;
+mod parser;
+mod tests;
+
+pub(crate) use self::cursor::Cursor;
+pub use self::ops::ExprVisitor;
+pub(crate) use self::parser::Parser;
+pub use self::parser::{parse, ParseError};
+pub use num_bigint::BigInt;
+
 use std::fmt;
 use std::rc::Rc;
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Expr {
     Var(Rc<Variable>),
     Wrd(Rc<Word>),
+    Num(Rc<Number>),
+    Str(Rc<Text>),
     Seq(Rc<Sequence>),
     Lst(Rc<List>),
 }
@@ -12,19 +25,25 @@ pub enum Expr {
 #[derive(Debug, PartialEq, Eq, Hash)]
 pub struct Variable(pub String);
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Eq, Hash)]
 pub struct Word(pub String);
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct Number(pub BigInt);
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct Text(pub String);
+
+#[derive(Debug, PartialEq, Eq, Hash)]
 pub struct Sequence(pub Vec<Expr>);
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Eq, Hash)]
 pub struct List {
     pub tag: String,
     pub pair: Option<Pair>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Eq, Hash)]
 pub struct Pair {
     pub head: Expr,
     pub tail: Expr,
@@ -35,6 +54,8 @@ impl fmt::Display for Expr {
         match self {
             Expr::Var(var) => var.fmt(f),
             Expr::Wrd(wrd) => wrd.fmt(f),
+            Expr::Num(num) => num.fmt(f),
+            Expr::Str(txt) => txt.fmt(f),
             Expr::Seq(seq) => seq.fmt(f),
             Expr::Lst(lst) => lst.fmt(f),
         }
@@ -62,27 +83,36 @@ impl Expr {
 
 impl fmt::Display for Sequence {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        for (i, expr) in self.0.iter().enumerate() {
-            expr.with_parens(f)?;
-            if i < self.0.len() - 1 {
-                write!(f, " ")?;
-            }
+        if self.0.len() < 2 {
+            self.with_parens(f)
+        } else {
+            self.items(f)
         }
-        Ok(())
     }
 }
 
 impl Sequence {
     fn with_parens(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "(")?;
-        fmt::Display::fmt(self, f)?;
+        self.items(f)?;
         write!(f, ")")
     }
+
+    fn items(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, expr) in self.0.iter().enumerate() {
+            expr.with_parens(f)?;
+            if i < self.0.len() - 1 {
+                write!(f, " ")?;
+            }
+        }
+        Ok(())
+    }
 }
 
 impl fmt::Display for List {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}[", self.tag)?;
+        write_name(f, &self.tag)?;
+        write!(f, "[")?;
         self.list_head(f, true)?;
         write!(f, "]")
     }
@@ -108,7 +138,51 @@ impl fmt::Display for Variable {
 }
 
 impl fmt::Display for Word {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0.is_empty() {
+            write!(f, "{}{}", parser::ESCAPE, parser::EMPTY)
+        } else {
+            write_name(f, &self.0)
+        }
+    }
+}
+
+fn write_name(f: &mut fmt::Formatter, name: &str) -> fmt::Result {
+    let is_integer = parser::is_integer(name);
+
+    for (i, c) in name.chars().enumerate() {
+        let is_special = c == parser::ESCAPE || parser::DELIMITERS.contains(c);
+
+        if is_special || c.is_whitespace() || (i == 0 && is_integer) {
+            write!(f, "{}", parser::ESCAPE)?;
+        }
+        write!(f, "{}", c)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for Number {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self.0)
     }
 }
+
+impl fmt::Display for Text {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "\"")?;
+
+        for c in self.0.chars() {
+            match c {
+                '"' => write!(f, "\\\"")?,
+                '\\' => write!(f, "\\\\")?,
+                '\n' => write!(f, "\\n")?,
+                '\r' => write!(f, "\\r")?,
+                '\t' => write!(f, "\\t")?,
+                _ if c.is_control() => write!(f, "\\u{{{:x}}}", c as u32)?,
+                _ => write!(f, "{}", c)?,
+            }
+        }
+
+        write!(f, "\"")
+    }
+}
@@ -0,0 +1,122 @@
+use super::*;
+use std::collections::HashMap;
+
+pub trait ExprVisitor {
+    fn enter(&mut self, _expr: &Expr) -> bool {
+        true
+    }
+
+    fn leave(&mut self, _expr: &Expr) {}
+}
+
+impl Expr {
+    pub fn variables(&self) -> Vec<Rc<Variable>> {
+        self.fold(Vec::new(), &mut |mut vars: Vec<Rc<Variable>>, expr| {
+            if let Expr::Var(var) = expr {
+                if !vars.contains(var) {
+                    vars.push(var.clone());
+                }
+            }
+            vars
+        })
+    }
+
+    pub fn is_ground(&self) -> bool {
+        match self {
+            Expr::Var(_) => false,
+            _ => self.children().iter().all(|child| child.is_ground()),
+        }
+    }
+
+    pub fn substitute(&self, substitution: &HashMap<String, Expr>) -> Expr {
+        self.map(&mut |expr| match &expr {
+            Expr::Var(var) => substitution.get(&var.0).cloned().unwrap_or(expr),
+            _ => expr,
+        })
+    }
+
+    pub fn size(&self) -> usize {
+        self.fold(0, &mut |size, _| size + 1)
+    }
+
+    pub fn depth(&self) -> usize {
+        let children = self.children().into_iter().map(Expr::depth);
+        children.max().unwrap_or(0) + 1
+    }
+
+    pub fn children(&self) -> Vec<&Expr> {
+        match self {
+            Expr::Seq(seq) => seq.0.iter().collect(),
+            Expr::Lst(lst) => {
+                let (mut items, tail) = lst.elements();
+                items.extend(tail);
+                items
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn map<F: FnMut(Expr) -> Expr>(&self, f: &mut F) -> Expr {
+        let expr = match self {
+            Expr::Seq(seq) => {
+                let items = seq.0.iter().map(|item| item.map(f));
+                Expr::Seq(Rc::new(Sequence(items.collect())))
+            }
+            Expr::Lst(lst) => {
+                let (items, tail) = lst.elements();
+                let items: Vec<_> = items.into_iter().map(|item| item.map(f)).collect();
+
+                let empty = Expr::Lst(Rc::new(List {
+                    tag: lst.tag.clone(),
+                    pair: None,
+                }));
+                let tail = tail.map_or(empty, |tail| tail.map(f));
+
+                items.into_iter().rev().fold(tail, |tail, head| {
+                    Expr::Lst(Rc::new(List {
+                        tag: lst.tag.clone(),
+                        pair: Some(Pair { head, tail }),
+                    }))
+                })
+            }
+            _ => self.clone(),
+        };
+
+        f(expr)
+    }
+
+    pub fn fold<T, F: FnMut(T, &Expr) -> T>(&self, init: T, f: &mut F) -> T {
+        let acc = f(init, self);
+        let children = self.children();
+        children
+            .into_iter()
+            .fold(acc, |acc, child| child.fold(acc, f))
+    }
+
+    pub fn visit<V: ExprVisitor + ?Sized>(&self, visitor: &mut V) {
+        if visitor.enter(self) {
+            for child in self.children() {
+                child.visit(visitor);
+            }
+        }
+        visitor.leave(self);
+    }
+}
+
+impl List {
+    fn elements(&self) -> (Vec<&Expr>, Option<&Expr>) {
+        let mut items = Vec::new();
+        let mut list = self;
+
+        while let Some(Pair { head, tail }) = &list.pair {
+            items.push(head);
+
+            match tail {
+                Expr::Lst(next) if next.tag == self.tag => list = next,
+                _ => return (items, Some(tail)),
+            }
+        }
+
+        (items, None)
+    }
+}
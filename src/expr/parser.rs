@@ -0,0 +1,280 @@
+use super::cursor::Cursor;
+use super::*;
+use std::error::Error;
+use std::str::FromStr;
+
+pub fn parse(input: &str) -> Result<Expr, ParseError> {
+    let mut parser = Parser::new(input);
+    let expr = parser.sequence()?;
+    parser.finish()?;
+    Ok(expr)
+}
+
+impl FromStr for Expr {
+    type Err = ParseError;
+
+    fn from_str(input: &str) -> Result<Expr, ParseError> {
+        parse(input)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl Error for ParseError {}
+
+pub(super) const DELIMITERS: &str = "$()[],|\"";
+
+// Inside a name, `\` makes the next character part of the name, so words
+// may contain spaces and delimiters, and `\&` stands for nothing at all so
+// that the empty word can be written. A name with any escape in it is
+// always a word, so `\42` is the word `42` rather than a number.
+pub(super) const ESCAPE: char = '\\';
+pub(super) const EMPTY: char = '&';
+
+pub struct Parser<'a> {
+    cursor: Cursor<'a>,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(input: &'a str) -> Parser<'a> {
+        Parser::at(input, 1, 1)
+    }
+
+    pub fn at(input: &'a str, line: usize, column: usize) -> Parser<'a> {
+        Parser {
+            cursor: Cursor::at(input, line, column),
+        }
+    }
+
+    pub fn sequence(&mut self) -> Result<Expr, ParseError> {
+        let mut items = self.terms()?;
+
+        match items.len() {
+            0 => Err(self.cursor.unexpected("an expression")),
+            1 => Ok(items.remove(0)),
+            _ => Ok(Expr::Seq(Rc::new(Sequence(items)))),
+        }
+    }
+
+    pub fn position(&mut self) -> (usize, usize) {
+        self.cursor.position()
+    }
+
+    pub fn skip(&mut self, c: char) -> bool {
+        self.cursor.skip(c)
+    }
+
+    pub fn is_done(&mut self) -> bool {
+        self.cursor.is_done()
+    }
+
+    pub fn finish(&mut self) -> Result<(), ParseError> {
+        if self.is_done() {
+            Ok(())
+        } else {
+            Err(self.cursor.unexpected("end of input"))
+        }
+    }
+
+    fn terms(&mut self) -> Result<Vec<Expr>, ParseError> {
+        let mut items = Vec::new();
+
+        loop {
+            self.cursor.skip_whitespace();
+
+            match self.cursor.peek() {
+                None | Some(')') | Some(']') | Some(',') | Some('|') => return Ok(items),
+                _ => items.push(self.term()?),
+            }
+        }
+    }
+
+    fn term(&mut self) -> Result<Expr, ParseError> {
+        match self.cursor.peek() {
+            Some('$') => {
+                self.cursor.advance();
+                let (name, _) = self.name("a variable name")?;
+                Ok(Expr::Var(Rc::new(Variable(name))))
+            }
+            Some('(') => {
+                self.cursor.advance();
+                let items = self.terms()?;
+                self.cursor.expect(')')?;
+                Ok(Expr::Seq(Rc::new(Sequence(items))))
+            }
+            Some('[') => self.list(String::new()),
+            Some('"') => self.string(),
+            _ => {
+                let (name, escaped) = self.name("an expression")?;
+
+                if self.cursor.peek() == Some(&'[') {
+                    self.list(name)
+                } else if !escaped && is_integer(&name) {
+                    Ok(Expr::Num(Rc::new(Number(name.parse().unwrap()))))
+                } else {
+                    Ok(Expr::Wrd(Rc::new(Word(name))))
+                }
+            }
+        }
+    }
+
+    fn list(&mut self, tag: String) -> Result<Expr, ParseError> {
+        let mut items = Vec::new();
+        let mut tail = None;
+
+        self.cursor.expect('[')?;
+        self.cursor.skip_whitespace();
+
+        if self.cursor.peek() == Some(&']') {
+            self.cursor.advance();
+        } else {
+            loop {
+                items.push(self.sequence()?);
+
+                match self.cursor.peek() {
+                    Some(',') => self.cursor.advance(),
+                    Some('|') => {
+                        self.cursor.advance();
+                        tail = Some(self.sequence()?);
+                        self.cursor.expect(']')?;
+                        break;
+                    }
+                    _ => {
+                        self.cursor.expect(']')?;
+                        break;
+                    }
+                }
+            }
+        }
+
+        let empty = Expr::Lst(Rc::new(List {
+            tag: tag.clone(),
+            pair: None,
+        }));
+
+        let list = items
+            .into_iter()
+            .rev()
+            .fold(tail.unwrap_or(empty), |tail, head| {
+                Expr::Lst(Rc::new(List {
+                    tag: tag.clone(),
+                    pair: Some(Pair { head, tail }),
+                }))
+            });
+
+        Ok(list)
+    }
+
+    fn string(&mut self) -> Result<Expr, ParseError> {
+        let mut text = String::new();
+
+        self.cursor.advance();
+
+        loop {
+            match self.cursor.peek() {
+                Some('"') => break,
+                Some('\\') => text.push(self.escape()?),
+                Some(&c) => {
+                    text.push(c);
+                    self.cursor.advance();
+                }
+                None => return Err(self.cursor.unexpected("'\"'")),
+            }
+        }
+        self.cursor.advance();
+
+        Ok(Expr::Str(Rc::new(Text(text))))
+    }
+
+    fn escape(&mut self) -> Result<char, ParseError> {
+        let (line, column) = self.cursor.location();
+        self.cursor.advance();
+
+        let c = match self.cursor.peek() {
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('u') => {
+                self.cursor.advance();
+                let code = self.code_point()?;
+
+                return std::char::from_u32(code).ok_or(ParseError {
+                    line,
+                    column,
+                    message: format!("invalid character code '{:x}'", code),
+                });
+            }
+            _ => return Err(self.cursor.unexpected("an escape sequence")),
+        };
+
+        self.cursor.advance();
+        Ok(c)
+    }
+
+    fn code_point(&mut self) -> Result<u32, ParseError> {
+        let mut code: u32 = 0;
+
+        self.cursor.exact('{')?;
+
+        for i in 0..6 {
+            match self.cursor.peek().and_then(|c| c.to_digit(16)) {
+                Some(digit) => code = code * 16 + digit,
+                None if i > 0 => break,
+                None => return Err(self.cursor.unexpected("a hexadecimal digit")),
+            }
+            self.cursor.advance();
+        }
+
+        self.cursor.exact('}')?;
+        Ok(code)
+    }
+
+    fn name(&mut self, expected: &str) -> Result<(String, bool), ParseError> {
+        let mut name = String::new();
+        let mut escaped = false;
+
+        while let Some(&c) = self.cursor.peek() {
+            if c.is_whitespace() || DELIMITERS.contains(c) {
+                break;
+            }
+            self.cursor.advance();
+
+            if c != ESCAPE {
+                name.push(c);
+                continue;
+            }
+
+            match self.cursor.peek() {
+                Some(&EMPTY) => {}
+                Some(&c) => name.push(c),
+                None => return Err(self.cursor.unexpected("an escaped character")),
+            }
+            self.cursor.advance();
+            escaped = true;
+        }
+
+        if name.is_empty() && !escaped {
+            Err(self.cursor.unexpected(expected))
+        } else {
+            Ok((name, escaped))
+        }
+    }
+}
+
+pub(super) fn is_integer(name: &str) -> bool {
+    let digits = name.strip_prefix('-').unwrap_or(name);
+    !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+}
@@ -0,0 +1,387 @@
+#![cfg(test)]
+
+use crate::expr::*;
+use crate::*;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+fn assert_round_trip(expr: Expr) {
+    let text = expr.to_string();
+    assert_eq!(parse(&text), Ok(expr), "round trip of {:?}", text);
+}
+
+#[test]
+fn parse_word() {
+    assert_eq!(parse("a"), Ok(expr!(wrd(a))));
+}
+
+#[test]
+fn parse_symbolic_word() {
+    assert_eq!(parse("<="), Ok(expr!(wrd(<=))));
+}
+
+#[test]
+fn parse_variable() {
+    assert_eq!(parse("$x"), Ok(expr!(var(x))));
+}
+
+#[test]
+fn parse_flat_sequence() {
+    assert_eq!(parse("a $x b"), Ok(expr!(seq(wrd(a), var(x), wrd(b)))));
+}
+
+#[test]
+fn parse_nested_sequences() {
+    assert_eq!(
+        parse("(a b) c (d (e f))"),
+        Ok(expr!(seq(
+            seq(wrd(a), wrd(b)),
+            wrd(c),
+            seq(wrd(d), seq(wrd(e), wrd(f)))
+        )))
+    );
+}
+
+#[test]
+fn parse_empty_and_singleton_sequences() {
+    assert_eq!(parse("()"), Ok(Expr::Seq(Rc::new(Sequence(vec![])))));
+    assert_eq!(parse("(a)"), Ok(expr!(seq(wrd(a)))));
+    assert_eq!(parse("((a))"), Ok(expr!(seq(seq(wrd(a))))));
+}
+
+#[test]
+fn parse_empty_list() {
+    assert_eq!(parse("k[]"), Ok(expr!(lst(k, []))));
+}
+
+#[test]
+fn parse_list_with_items() {
+    assert_eq!(
+        parse("λ[a, b c, $x]"),
+        Ok(expr!(lst(λ, [wrd(a), seq(wrd(b), wrd(c)), var(x),])))
+    );
+}
+
+#[test]
+fn parse_list_with_tail() {
+    assert_eq!(
+        parse("k[a, b | $x]"),
+        Ok(expr!(lst(k, [wrd(a), wrd(b) | var(x)])))
+    );
+}
+
+#[test]
+fn parse_untagged_list() {
+    let expr = parse("[a]").unwrap();
+
+    if let Expr::Lst(lst) = expr {
+        assert_eq!(lst.tag, "");
+    } else {
+        panic!("expected a list, got {:?}", expr);
+    }
+}
+
+#[test]
+fn parse_with_surrounding_whitespace() {
+    assert_eq!(parse("  ( a\n  b )\t"), Ok(expr!(seq(wrd(a), wrd(b)))));
+}
+
+#[test]
+fn parse_from_str() {
+    let expr: Expr = "$x sub $y".parse().unwrap();
+    assert_eq!(expr, expr!(seq(var(x), wrd(sub), var(y))));
+}
+
+#[test]
+fn round_trip_expressions() {
+    assert_round_trip(expr!(wrd(a)));
+    assert_round_trip(expr!(var(x)));
+    assert_round_trip(expr!(seq(wrd(a))));
+    assert_round_trip(expr!(seq(seq(wrd(a)), wrd(b))));
+    assert_round_trip(expr!(seq(
+        seq(var(y), wrd(b)),
+        var(x),
+        seq(wrd(a), seq(var(z), wrd(c)))
+    )));
+    assert_round_trip(expr!(lst(λ, [])));
+    assert_round_trip(expr!(lst(λ, [seq(wrd(a)), seq(wrd(b), wrd(c)),])));
+    assert_round_trip(expr!(lst(λ, [var(head) | var(tail)])));
+    assert_round_trip(expr!(lst(a, [wrd(x) | lst(b, [wrd(y),])])));
+    assert_round_trip(expr!(seq(
+        lst(λ, [wrd(a), wrd(b),]),
+        wrd(+),
+        var(list),
+        wrd(=),
+        lst(λ, [var(head) | var(rest)])
+    )));
+}
+
+#[test]
+fn round_trip_words_that_look_like_numbers() {
+    assert_round_trip(expr!(seq(wrd(n), wrd(1), num(1))));
+    assert_round_trip(Expr::Wrd(Rc::new(Word(String::from("-7")))));
+    assert_round_trip(Expr::Wrd(Rc::new(Word(String::from("\\42")))));
+    assert_round_trip(Expr::Wrd(Rc::new(Word(String::from("\\a")))));
+
+    assert_eq!(expr!(wrd(1)).to_string(), "\\1");
+    assert_eq!(parse("\\1").unwrap(), expr!(wrd(1)));
+    assert_eq!(parse("1").unwrap(), expr!(num(1)));
+}
+
+#[test]
+fn round_trip_words_with_spaces_and_delimiters() {
+    let word = |name: &str| Expr::Wrd(Rc::new(Word(String::from(name))));
+
+    for name in &[
+        "a b",
+        "",
+        "a[",
+        "(x)",
+        "$x",
+        "a|b",
+        "back\\slash",
+        "\\&",
+        "tab\t",
+    ] {
+        assert_round_trip(word(name));
+    }
+
+    let list = Expr::Lst(Rc::new(List {
+        tag: String::from("a b"),
+        pair: Some(Pair {
+            head: word("x y"),
+            tail: Expr::Lst(Rc::new(List {
+                tag: String::from("a b"),
+                pair: None,
+            })),
+        }),
+    }));
+    assert_round_trip(list);
+
+    assert_eq!(word("a b").to_string(), "a\\ b");
+    assert_eq!(word("").to_string(), "\\&");
+    assert_eq!(
+        parse("a\\ b c"),
+        Ok(Expr::Seq(Rc::new(Sequence(vec![word("a b"), word("c")]))))
+    );
+}
+
+#[test]
+fn round_trip_words_that_look_like_strings() {
+    let word = expr!(wrd("hi there"));
+
+    assert_eq!(word, Expr::Wrd(Rc::new(Word(String::from("\"hi there\"")))));
+    assert_eq!(word.to_string(), "\\\"hi\\ there\\\"");
+    assert_round_trip(word);
+    assert_round_trip(expr!(seq(wrd(say), wrd("hi"), str("hi"))));
+}
+
+#[test]
+fn reject_empty_input() {
+    let error = parse("  ").unwrap_err();
+    assert_eq!((error.line, error.column), (1, 3));
+    assert_eq!(error.message, "expected an expression, found end of input");
+}
+
+#[test]
+fn reject_unclosed_sequence() {
+    let error = parse("(a b").unwrap_err();
+    assert_eq!((error.line, error.column), (1, 5));
+    assert_eq!(error.message, "expected ')', found end of input");
+}
+
+#[test]
+fn reject_mismatched_brackets() {
+    let error = parse("k[a, b)").unwrap_err();
+    assert_eq!((error.line, error.column), (1, 7));
+    assert_eq!(error.message, "expected ']', found ')'");
+}
+
+#[test]
+fn reject_missing_variable_name() {
+    let error = parse("a\n  $ b").unwrap_err();
+    assert_eq!((error.line, error.column), (2, 4));
+    assert_eq!(error.message, "expected a variable name, found ' '");
+}
+
+#[test]
+fn reject_trailing_input() {
+    let error = parse("a b) c").unwrap_err();
+    assert_eq!((error.line, error.column), (1, 4));
+    assert_eq!(error.to_string(), "1:4: expected end of input, found ')'");
+}
+
+#[test]
+fn reject_empty_list_item() {
+    let error = parse("k[a, ]").unwrap_err();
+    assert_eq!((error.line, error.column), (1, 6));
+    assert_eq!(error.message, "expected an expression, found ']'");
+}
+
+#[test]
+fn parse_numbers() {
+    assert_eq!(parse("42"), Ok(expr!(num(42))));
+    assert_eq!(parse("-7"), Ok(expr!(num(-7))));
+    assert_eq!(
+        parse("123456789012345678901234567890"),
+        Ok(Expr::Num(Rc::new(Number(
+            "123456789012345678901234567890".parse().unwrap()
+        ))))
+    );
+}
+
+#[test]
+fn parse_words_containing_digits() {
+    assert_eq!(parse("-"), Ok(expr!(wrd(-))));
+    assert_eq!(parse("x1"), Ok(expr!(wrd(x1))));
+    assert_eq!(
+        parse("1-2"),
+        Ok(Expr::Wrd(Rc::new(Word(String::from("1-2")))))
+    );
+}
+
+#[test]
+fn round_trip_numbers() {
+    assert_round_trip(expr!(seq(num(1), wrd(+), num(-2))));
+    assert_round_trip(expr!(lst(k, [num(0), num(10),])));
+}
+
+#[test]
+fn parse_strings() {
+    assert_eq!(parse(r#""hello world""#), Ok(expr!(str("hello world"))));
+    assert_eq!(parse(r#""""#), Ok(expr!(str(""))));
+    assert_eq!(
+        parse(r#"say "a $x (b)" k[c]"#),
+        Ok(expr!(seq(wrd(say), str("a $x (b)"), lst(k, [wrd(c),]))))
+    );
+}
+
+#[test]
+fn parse_string_escapes() {
+    assert_eq!(
+        parse(r#""\"quoted\" \\ \n\r\t \u{1F600}""#),
+        Ok(expr!(str("\"quoted\" \\ \n\r\t \u{1F600}")))
+    );
+}
+
+#[test]
+fn display_strings_with_escapes() {
+    assert_eq!(
+        expr!(str("a \"b\"\\\n\u{7}")).to_string(),
+        r#""a \"b\"\\\n\u{7}""#
+    );
+}
+
+#[test]
+fn round_trip_strings() {
+    assert_round_trip(expr!(str("")));
+    assert_round_trip(expr!(seq(str("a b"), wrd(=), str("[$x, \"y\"]"))));
+    assert_round_trip(expr!(str("tab\there\u{0}\u{10FFFF}")));
+}
+
+#[test]
+fn reject_unterminated_string() {
+    let error = parse(r#"say "hello"#).unwrap_err();
+    assert_eq!((error.line, error.column), (1, 11));
+    assert_eq!(error.message, "expected '\"', found end of input");
+}
+
+#[test]
+fn reject_unknown_escape() {
+    let error = parse(r#""a\qb""#).unwrap_err();
+    assert_eq!((error.line, error.column), (1, 4));
+    assert_eq!(error.message, "expected an escape sequence, found 'q'");
+}
+
+#[test]
+fn reject_invalid_character_code() {
+    let error = parse(r#""a\u{d800}""#).unwrap_err();
+    assert_eq!((error.line, error.column), (1, 3));
+    assert_eq!(error.message, "invalid character code 'd800'");
+
+    let error = parse(r#""\u{}""#).unwrap_err();
+    assert_eq!(error.message, "expected a hexadecimal digit, found '}'");
+}
+
+#[test]
+fn collect_variables_in_order_of_occurrence() {
+    let expr = parse("$y (a $x) λ[$y, $z | $x]").unwrap();
+    let names: Vec<_> = expr.variables().iter().map(|var| var.0.clone()).collect();
+
+    assert_eq!(names, vec!["y", "x", "z"]);
+}
+
+#[test]
+fn check_whether_expressions_are_ground() {
+    assert!(parse("a (b 1) \"c\" λ[d, e]").unwrap().is_ground());
+    assert!(!parse("a (b $x)").unwrap().is_ground());
+    assert!(!parse("λ[a | $tail]").unwrap().is_ground());
+}
+
+#[test]
+fn substitute_variables() {
+    let mut substitution = HashMap::new();
+    substitution.insert(String::from("x"), parse("(f $y)").unwrap());
+    substitution.insert(String::from("t"), parse("λ[c]").unwrap());
+
+    let expr = parse("$x $y λ[$x | $t]").unwrap();
+
+    assert_eq!(
+        expr.substitute(&substitution),
+        parse("(f $y) $y λ[(f $y), c]").unwrap()
+    );
+}
+
+#[test]
+fn measure_size_and_depth() {
+    assert_eq!(parse("a").unwrap().size(), 1);
+    assert_eq!(parse("a").unwrap().depth(), 1);
+
+    let expr = parse("a (b (c d)) λ[e, f]").unwrap();
+    assert_eq!(expr.size(), 10);
+    assert_eq!(expr.depth(), 4);
+}
+
+#[test]
+fn map_expressions_bottom_up() {
+    let expr = parse("a (b a) k[a | $t]").unwrap();
+
+    let mapped = expr.map(&mut |expr| match &expr {
+        Expr::Wrd(wrd) if wrd.0 == "a" => expr!(wrd(z)),
+        Expr::Seq(seq) if seq.0.len() == 2 => {
+            Expr::Seq(Rc::new(Sequence(seq.0.iter().rev().cloned().collect())))
+        }
+        _ => expr,
+    });
+
+    assert_eq!(mapped, parse("z (z b) k[z | $t]").unwrap());
+}
+
+#[test]
+fn fold_and_visit_subexpressions() {
+    let expr = parse("add 1 (add 2 3)").unwrap();
+
+    let total = expr.fold(BigInt::from(0), &mut |total, expr| match expr {
+        Expr::Num(num) => total + &num.0,
+        _ => total,
+    });
+    assert_eq!(total, BigInt::from(6));
+
+    struct Words(Vec<String>);
+
+    impl ExprVisitor for Words {
+        fn enter(&mut self, expr: &Expr) -> bool {
+            match expr {
+                Expr::Wrd(wrd) => self.0.push(wrd.0.clone()),
+                Expr::Seq(seq) => return seq.0.first() != Some(&expr!(wrd(d))),
+                _ => {}
+            }
+            true
+        }
+    }
+
+    let mut words = Words(Vec::new());
+    parse("a (b c) (d (e) f) g").unwrap().visit(&mut words);
+
+    assert_eq!(words.0, vec!["a", "b", "c", "g"]);
+}
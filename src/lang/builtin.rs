@@ -0,0 +1,201 @@
+use crate::expr::*;
+use crate::state::State;
+use num_bigint::Sign;
+use std::rc::Rc;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Num,
+    Str,
+}
+
+const NUMBERS: [Kind; 3] = [Kind::Num; 3];
+const STRINGS: [Kind; 3] = [Kind::Str; 3];
+
+type Eval = fn(&State, usize, &[&Expr]) -> Vec<State>;
+
+const RELATIONS: [(&str, &[Kind], Eval); 5] = [
+    ("add", &NUMBERS, add),
+    ("sub", &NUMBERS, sub),
+    ("mul", &NUMBERS, mul),
+    ("concat", &STRINGS, concat),
+    ("length", &[Kind::Str, Kind::Num], length),
+];
+
+type Test = fn(&BigInt, &BigInt) -> bool;
+
+const COMPARISONS: [(&str, Test); 4] = [
+    ("<", BigInt::lt),
+    ("<=", BigInt::le),
+    (">", BigInt::gt),
+    (">=", BigInt::ge),
+];
+
+pub fn evaluate(
+    state: &State,
+    (scope, target): (usize, &Expr),
+) -> Option<(&'static str, Vec<State>)> {
+    let items = match target {
+        Expr::Seq(seq) => seq.0.as_slice(),
+        _ => return None,
+    };
+
+    if let [Expr::Wrd(op), args @ ..] = items {
+        let args: Vec<_> = args.iter().collect();
+
+        let relation = RELATIONS
+            .iter()
+            .find(|(name, kinds, _)| op.0 == *name && applies(state, scope, &args, kinds));
+
+        if let Some((name, _, eval)) = relation {
+            return Some((name, eval(state, scope, &args)));
+        }
+    }
+
+    match items {
+        [x, Expr::Wrd(op), y] if op.0 == "!=" => {
+            let states = state.disunify((scope, x), (scope, y));
+            Some(("!=", states.into_iter().collect()))
+        }
+        [x, Expr::Wrd(op), y] => {
+            let (name, test) = COMPARISONS.iter().find(|(name, _)| op.0 == *name)?;
+
+            if applies(state, scope, &[x, y], &[Kind::Num; 2]) {
+                Some((name, compare(state, scope, &[x, y], *test)))
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+// A built-in only takes over a goal when its arguments are values of the
+// kinds it computes with, or unknowns, and at least one is such a value.
+// Any other goal with the same name is left to the rules, so that rule sets
+// can define their own `add` over `z` and `s $n`, or `<` over words.
+fn applies(state: &State, scope: usize, args: &[&Expr], kinds: &[Kind]) -> bool {
+    if args.len() != kinds.len() {
+        return false;
+    }
+
+    let values = args.iter().map(|arg| state.resolve_var((scope, arg)));
+    let mut known = false;
+
+    for ((_, value), kind) in values.zip(kinds) {
+        match (value, kind) {
+            (Expr::Var(_), _) => {}
+            (Expr::Num(_), Kind::Num) | (Expr::Str(_), Kind::Str) => known = true,
+            _ => return false,
+        }
+    }
+
+    known
+}
+
+fn add(state: &State, scope: usize, args: &[&Expr]) -> Vec<State> {
+    match numbers(state, scope, args).as_slice() {
+        [Some(a), Some(b), _] => bind(state, scope, args[2], a + b),
+        [Some(a), None, Some(c)] => bind(state, scope, args[1], c - a),
+        [None, Some(b), Some(c)] => bind(state, scope, args[0], c - b),
+        _ => Vec::new(),
+    }
+}
+
+fn sub(state: &State, scope: usize, args: &[&Expr]) -> Vec<State> {
+    add(state, scope, &[args[1], args[2], args[0]])
+}
+
+fn mul(state: &State, scope: usize, args: &[&Expr]) -> Vec<State> {
+    match numbers(state, scope, args).as_slice() {
+        [Some(a), Some(b), _] => bind(state, scope, args[2], a * b),
+        [Some(a), None, Some(c)] => divide(state, scope, args[1], c, a),
+        [None, Some(b), Some(c)] => divide(state, scope, args[0], c, b),
+        _ => Vec::new(),
+    }
+}
+
+fn divide(state: &State, scope: usize, var: &Expr, n: &BigInt, d: &BigInt) -> Vec<State> {
+    if d.sign() != Sign::NoSign {
+        if (n % d).sign() == Sign::NoSign {
+            return bind(state, scope, var, n / d);
+        }
+    } else if n.sign() == Sign::NoSign {
+        return vec![state.clone()];
+    }
+    Vec::new()
+}
+
+fn concat(state: &State, scope: usize, args: &[&Expr]) -> Vec<State> {
+    match texts(state, scope, args).as_slice() {
+        [Some(a), Some(b), _] => bind_text(state, scope, args[2], format!("{}{}", a, b)),
+        [Some(a), None, Some(c)] => match c.strip_prefix(a.as_str()) {
+            Some(b) => bind_text(state, scope, args[1], String::from(b)),
+            None => Vec::new(),
+        },
+        [None, Some(b), Some(c)] => match c.strip_suffix(b.as_str()) {
+            Some(a) => bind_text(state, scope, args[0], String::from(a)),
+            None => Vec::new(),
+        },
+        [None, None, Some(c)] => {
+            let splits = c.char_indices().map(|(i, _)| i).chain(Some(c.len()));
+
+            let states = splits.flat_map(|i| {
+                let states = bind_text(state, scope, args[0], String::from(&c[..i]));
+                let suffix = String::from(&c[i..]);
+
+                states
+                    .into_iter()
+                    .flat_map(move |state| bind_text(&state, scope, args[1], suffix.clone()))
+            });
+            states.collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn length(state: &State, scope: usize, args: &[&Expr]) -> Vec<State> {
+    match texts(state, scope, args).as_slice() {
+        [Some(text), _] => bind(state, scope, args[1], BigInt::from(text.chars().count())),
+        _ => Vec::new(),
+    }
+}
+
+fn compare(state: &State, scope: usize, args: &[&Expr], test: Test) -> Vec<State> {
+    match numbers(state, scope, args).as_slice() {
+        [Some(a), Some(b)] if test(a, b) => vec![state.clone()],
+        _ => Vec::new(),
+    }
+}
+
+fn numbers(state: &State, scope: usize, args: &[&Expr]) -> Vec<Option<BigInt>> {
+    let values = args.iter().map(|arg| state.resolve_scoped(arg, scope));
+
+    let numbers = values.map(|value| match value {
+        Expr::Num(num) => Some(num.0.clone()),
+        _ => None,
+    });
+    numbers.collect()
+}
+
+fn bind(state: &State, scope: usize, var: &Expr, value: BigInt) -> Vec<State> {
+    let value = Expr::Num(Rc::new(Number(value)));
+    let states = state.unify((scope, var), (scope, &value));
+    states.into_iter().collect()
+}
+
+fn texts(state: &State, scope: usize, args: &[&Expr]) -> Vec<Option<String>> {
+    let values = args.iter().map(|arg| state.resolve_scoped(arg, scope));
+
+    let texts = values.map(|value| match value {
+        Expr::Str(txt) => Some(txt.0.clone()),
+        _ => None,
+    });
+    texts.collect()
+}
+
+fn bind_text(state: &State, scope: usize, var: &Expr, value: String) -> Vec<State> {
+    let value = Expr::Str(Rc::new(Text(value)));
+    let states = state.unify((scope, var), (scope, &value));
+    states.into_iter().collect()
+}
@@ -0,0 +1,137 @@
+use super::search::{Search, SearchStrategy};
+use super::{builtin, negated, RuleSet, NOT};
+use crate::expr::Expr;
+use crate::proof::{Proof, ProofTree};
+use std::error::Error;
+use std::fmt;
+use std::iter;
+
+#[derive(Debug, PartialEq)]
+pub enum CheckError {
+    UnknownRule(String),
+    PremiseCount {
+        rule: String,
+        expected: usize,
+        found: usize,
+    },
+    Mismatch {
+        rule: String,
+        conclusion: Expr,
+    },
+    Derivable(Expr),
+    Undecided(Expr),
+}
+
+impl fmt::Display for CheckError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CheckError::UnknownRule(rule) => write!(f, "unknown rule '{}'", rule),
+            CheckError::PremiseCount {
+                rule,
+                expected,
+                found,
+            } => write!(
+                f,
+                "rule '{}' has {} premises, but the proof gives {}",
+                rule, expected, found
+            ),
+            CheckError::Mismatch { rule, conclusion } => {
+                write!(f, "rule '{}' does not prove '{}'", rule, conclusion)
+            }
+            CheckError::Derivable(goal) => write!(f, "'{}' is derivable", goal),
+            CheckError::Undecided(goal) => {
+                write!(f, "'{}' is not derivable within the search depth", goal)
+            }
+        }
+    }
+}
+
+impl Error for CheckError {}
+
+impl RuleSet {
+    pub fn check(&self, proof: &Proof) -> Result<(), CheckError> {
+        self.check_tree(&proof.to_tree())
+    }
+
+    pub fn check_tree(&self, tree: &ProofTree) -> Result<(), CheckError> {
+        if tree.rule == NOT && tree.premises.is_empty() {
+            if let Some(goal) = negated(&tree.conclusion) {
+                return self.check_negation(&goal);
+            }
+        }
+
+        if let Some((name, states)) =
+            builtin::evaluate(&self.initial_state(), (0, &tree.conclusion))
+        {
+            if name == tree.rule && tree.premises.is_empty() {
+                return if states.iter().any(|state| state.bindings(0).is_empty()) {
+                    Ok(())
+                } else {
+                    Err(mismatch(tree))
+                };
+            }
+        }
+
+        self.check_rule(tree)?;
+
+        for premise in &tree.premises {
+            self.check_tree(premise)?;
+        }
+
+        Ok(())
+    }
+
+    fn check_rule(&self, tree: &ProofTree) -> Result<(), CheckError> {
+        let rule = match self.rules.get(&tree.rule) {
+            Some(rule) => rule,
+            None => return Err(CheckError::UnknownRule(tree.rule.clone())),
+        };
+
+        if rule.premises.len() != tree.premises.len() {
+            return Err(CheckError::PremiseCount {
+                rule: tree.rule.clone(),
+                expected: rule.premises.len(),
+                found: tree.premises.len(),
+            });
+        }
+
+        let mut state = self.initial_state();
+        let scope = state.new_scope();
+
+        let pairs = rule.premises.iter().zip(&tree.premises);
+        let pairs = pairs.map(|(premise, tree)| (premise, &tree.conclusion));
+
+        let state = iter::once((&rule.conclusion, &tree.conclusion))
+            .chain(pairs)
+            .try_fold(state, |state, (expr, recorded)| {
+                state.unify((scope, expr), (0, recorded))
+            });
+
+        match state {
+            Some(state) if state.bindings(0).is_empty() => Ok(()),
+            _ => Err(mismatch(tree)),
+        }
+    }
+
+    fn check_negation(&self, goal: &Expr) -> Result<(), CheckError> {
+        let search = Search::new(SearchStrategy::DepthFirst);
+        let state = self.initial_state();
+        let depth = Some(self.negation_depth);
+
+        match self
+            .derive_in_state(&search, &state, (0, goal), depth)
+            .next()
+        {
+            Some(_) => Err(CheckError::Derivable(goal.clone())),
+            None if search.is_pruned() => Err(CheckError::Undecided(goal.clone())),
+            None => Ok(()),
+        }
+    }
+}
+
+fn mismatch(tree: &ProofTree) -> CheckError {
+    CheckError::Mismatch {
+        rule: tree.rule.clone(),
+        conclusion: tree.conclusion.clone(),
+    }
+}
@@ -0,0 +1,142 @@
+use crate::expr::*;
+use crate::state::State;
+use std::collections::HashMap;
+
+#[derive(Debug, Eq, Hash, PartialEq)]
+enum Symbol {
+    Wrd(String),
+    Num(BigInt),
+    Str(String),
+    Seq(usize, Option<String>),
+    Lst(String),
+}
+
+#[derive(Default)]
+pub(super) struct Index {
+    root: Column,
+    sequences: HashMap<usize, Sequences>,
+}
+
+#[derive(Default)]
+struct Sequences {
+    all: Vec<usize>,
+    columns: Vec<Column>,
+}
+
+#[derive(Default)]
+struct Column {
+    symbols: HashMap<Symbol, Vec<usize>>,
+    lengths: HashMap<usize, Vec<usize>>,
+    any: Vec<usize>,
+}
+
+impl Index {
+    pub fn insert(&mut self, position: usize, conclusion: &Expr) {
+        let items = match conclusion {
+            Expr::Seq(seq) => &seq.0,
+            _ => return self.root.insert(position, conclusion),
+        };
+
+        let sequences = self.sequences.entry(items.len()).or_default();
+        sequences.all.push(position);
+        sequences.columns.resize_with(items.len(), Column::default);
+
+        for (column, item) in sequences.columns.iter_mut().zip(items) {
+            column.insert(position, item);
+        }
+    }
+
+    pub fn candidates(&self, state: &State, target: (usize, &Expr)) -> Option<Vec<usize>> {
+        let (scope, target) = state.resolve_var(target);
+
+        let mut positions = match &target {
+            Expr::Seq(seq) => match self.sequences.get(&seq.0.len()) {
+                Some(sequences) => sequences.candidates(state, scope, &seq.0),
+                None => Vec::new(),
+            },
+            _ => self.root.candidates(state, (scope, &target))?,
+        };
+
+        positions.extend(&self.root.any);
+        positions.sort_unstable();
+        positions.dedup();
+
+        Some(positions)
+    }
+}
+
+impl Sequences {
+    fn candidates(&self, state: &State, scope: usize, items: &[Expr]) -> Vec<usize> {
+        let columns = self.columns.iter().zip(items);
+        let candidates =
+            columns.filter_map(|(column, item)| column.candidates(state, (scope, item)));
+
+        candidates
+            .min_by_key(Vec::len)
+            .unwrap_or_else(|| self.all.clone())
+    }
+}
+
+impl Column {
+    fn insert(&mut self, position: usize, expr: &Expr) {
+        match symbol(expr, Expr::clone) {
+            Some(symbol) => {
+                if let Symbol::Seq(length, _) = symbol {
+                    self.lengths.entry(length).or_default().push(position);
+                }
+                self.symbols.entry(symbol).or_default().push(position);
+            }
+            None => self.any.push(position),
+        }
+    }
+
+    fn candidates(&self, state: &State, (scope, expr): (usize, &Expr)) -> Option<Vec<usize>> {
+        let (scope, expr) = state.resolve_var((scope, expr));
+        let resolve = |item: &Expr| state.resolve_var((scope, item)).1;
+
+        let mut positions = match symbol(&expr, resolve)? {
+            Symbol::Seq(length, Some(word)) => {
+                let mut positions = self.positions(&Symbol::Seq(length, None));
+                positions.extend(self.positions(&Symbol::Seq(length, Some(word))));
+                positions
+            }
+            Symbol::Seq(length, None) if leads_with_var(state, scope, &expr) => {
+                self.lengths.get(&length).cloned().unwrap_or_default()
+            }
+            symbol => self.positions(&symbol),
+        };
+
+        positions.extend(&self.any);
+        positions.sort_unstable();
+
+        Some(positions)
+    }
+
+    fn positions(&self, symbol: &Symbol) -> Vec<usize> {
+        self.symbols.get(symbol).cloned().unwrap_or_default()
+    }
+}
+
+fn symbol<F: Fn(&Expr) -> Expr>(expr: &Expr, resolve: F) -> Option<Symbol> {
+    let symbol = match expr {
+        Expr::Var(_) => return None,
+        Expr::Wrd(wrd) => Symbol::Wrd(wrd.0.clone()),
+        Expr::Num(num) => Symbol::Num(num.0.clone()),
+        Expr::Str(txt) => Symbol::Str(txt.0.clone()),
+        Expr::Seq(seq) => match seq.0.first().map(resolve) {
+            Some(Expr::Wrd(wrd)) => Symbol::Seq(seq.0.len(), Some(wrd.0.clone())),
+            _ => Symbol::Seq(seq.0.len(), None),
+        },
+        Expr::Lst(lst) => Symbol::Lst(lst.tag.clone()),
+    };
+    Some(symbol)
+}
+
+fn leads_with_var(state: &State, scope: usize, expr: &Expr) -> bool {
+    if let Expr::Seq(seq) = expr {
+        if let Some(first) = seq.0.first() {
+            return matches!(state.resolve_var((scope, first)).1, Expr::Var(_));
+        }
+    }
+    false
+}
@@ -0,0 +1,132 @@
+//! `RuleSet::derive_with_limits` runs a search under a budget of depth,
+//! steps and solutions, and reports through `Status` why it stopped. It is
+//! not simply `derive` with budgets attached:
+//!
+//! - It always uses the default search strategy, and its depth limit applies
+//!   to the whole proof, as with a single pass of iterative deepening.
+//! - Resuming with a new depth or step limit restarts the search, and proofs
+//!   already reported are skipped. Two proofs count as the same when their
+//!   `ProofTree`s are equal, that is when they apply the same rules to reach
+//!   the same conclusions.
+//! - Negated premises are searched within the remaining depth, and tabled
+//!   goals are filled within it, so either may end the search with
+//!   `Status::DepthLimit` where `derive` would keep going.
+
+use super::search::{Search, SearchStrategy};
+use super::RuleSet;
+use crate::expr::Expr;
+use crate::iter::BoxIter;
+use crate::proof::{Proof, ProofTree};
+use crate::state::State;
+use std::collections::HashSet;
+use std::iter;
+use std::rc::Rc;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Limits {
+    pub max_depth: Option<usize>,
+    pub max_steps: Option<usize>,
+    pub max_solutions: Option<usize>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Status {
+    Searching,
+    Exhausted,
+    DepthLimit,
+    StepLimit,
+    SolutionLimit,
+}
+
+pub struct Derivation<'a> {
+    rule_set: &'a RuleSet,
+    target: Expr,
+    limits: Limits,
+    search: Search,
+    stream: BoxIter<'a, (State, Rc<Proof>)>,
+    reported: HashSet<ProofTree>,
+    status: Status,
+}
+
+impl<'a> Derivation<'a> {
+    pub(super) fn new(rule_set: &'a RuleSet, target: &Expr, limits: Limits) -> Derivation<'a> {
+        let mut derivation = Derivation {
+            rule_set,
+            target: target.clone(),
+            limits,
+            search: Search::new(SearchStrategy::default()),
+            stream: Box::new(iter::empty()),
+            reported: HashSet::new(),
+            status: Status::Searching,
+        };
+
+        derivation.restart();
+        derivation
+    }
+
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    pub fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    pub fn resume(&mut self, limits: Limits) {
+        let same_search =
+            limits.max_depth == self.limits.max_depth && limits.max_steps == self.limits.max_steps;
+
+        self.limits = limits;
+
+        if self.status == Status::SolutionLimit && same_search {
+            self.status = Status::Searching;
+        } else if self.status != Status::Exhausted {
+            self.restart();
+        }
+    }
+
+    fn restart(&mut self) {
+        let state = self.rule_set.initial_state();
+        let target = (0, &self.target);
+        let depth = self.limits.max_depth;
+
+        self.search = Search::limited(SearchStrategy::default(), self.limits.max_steps);
+        self.stream = self
+            .rule_set
+            .derive_in_state(&self.search, &state, target, depth);
+        self.status = Status::Searching;
+    }
+}
+
+impl Iterator for Derivation<'_> {
+    type Item = (State, Rc<Proof>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.status != Status::Searching {
+            return None;
+        }
+
+        if let Some(max) = self.limits.max_solutions {
+            if self.reported.len() >= max {
+                self.status = Status::SolutionLimit;
+                return None;
+            }
+        }
+
+        for (state, proof) in &mut self.stream {
+            if self.reported.insert(proof.to_tree()) {
+                return Some((state, proof));
+            }
+        }
+
+        self.status = if self.search.is_halted() {
+            Status::StepLimit
+        } else if self.search.is_pruned() {
+            Status::DepthLimit
+        } else {
+            Status::Exhausted
+        };
+
+        None
+    }
+}
@@ -0,0 +1,362 @@
+//! Rule files hold a sequence of named rules. Each rule starts with a `rule`
+//! line giving its name, followed by its premises and conclusion in one of
+//! two forms:
+//!
+//! ```text
+//! # Comments run from `#` to the end of the line.
+//!
+//! rule append-0
+//!     λ[] + $list = $list
+//!
+//! rule append-N
+//!     $tail + $list = $rest
+//!     ------------------------------------------
+//!     λ[$head | $tail] + $list = λ[$head | $rest]
+//!
+//! rule type-append
+//!     ($a + $b) : List :- $a : List, $b : List
+//! ```
+//!
+//! Premises above a divider (three or more `-` or `─`) may be written one per
+//! line or separated by commas, and the line below the divider holds the
+//! conclusion. In the `:-` form the conclusion comes first, and its premises
+//! follow separated by commas or line breaks.
+//!
+//! Some premise forms are handled by the engine rather than by searching
+//! rules: `$a != $b` requires its two sides to remain distinct, and
+//! `not P` holds when `P` cannot be derived. Everything after `not` is
+//! negated as one goal, so `not $x in $c` means the same as
+//! `not ($x in $c)`.
+//!
+//! Integers such as `42` or `-7` are numbers rather than words; a word made
+//! of digits is written with a leading backslash, as in `\42`. The same
+//! backslash puts spaces, quotes, brackets and other delimiters into a
+//! word, as in `a\ b` or `\"hi\"`, and `\&` is the empty word. These
+//! built-in relations compute with numbers:
+//!
+//! - `add $a $b $c` holds when `$a + $b = $c`, and `sub $a $b $c` when
+//!   `$a - $b = $c`; either finds one unknown from the other two.
+//! - `mul $a $b $c` holds when `$a * $b = $c`, finding `$c` from its factors
+//!   or a factor by exact division.
+//! - `$a < $b`, `$a <= $b`, `$a > $b` and `$a >= $b` compare two numbers.
+//!
+//! Quoted strings such as `"hello, world"` may contain spaces, brackets, `$`
+//! and `#`, with `\"`, `\\`, `\n`, `\r`, `\t` and `\u{...}` escapes. They
+//! have built-in relations too:
+//!
+//! - `concat $a $b $c` holds when `$c` is `$a` followed by `$b`; it finds one
+//!   unknown from the other two, or every split of `$c` when only it is known.
+//! - `length $s $n` holds when `$s` has `$n` characters.
+//!
+//! A built-in whose arguments are not known well enough has no solutions.
+//! Built-ins only apply to goals whose arguments are numbers (or strings)
+//! and unknowns; other goals with the same name, such as `add z $n $n` or
+//! `a < b`, are matched against the rules like any other.
+
+use super::{Rule, RuleSet};
+use crate::expr::{Expr, ParseError, Parser};
+use std::cmp;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::iter;
+use std::path::{Path, PathBuf};
+
+const COMMENT: char = '#';
+const QUOTE: char = '"';
+const HEADER: &str = "rule";
+const TURNSTILE: &str = ":-";
+
+impl RuleSet {
+    pub fn load_str(&mut self, source: &str) -> Result<(), ParseError> {
+        for (name, conclusion, premises) in parse_rules(source)? {
+            self.insert(&name, &conclusion, &premises);
+        }
+        Ok(())
+    }
+
+    pub fn load_file<P: AsRef<Path>>(&mut self, path: P) -> Result<(), LoadError> {
+        let path = path.as_ref();
+
+        let source =
+            fs::read_to_string(path).map_err(|err| LoadError::Io(path.to_path_buf(), err))?;
+
+        self.load_str(&source)
+            .map_err(|err| LoadError::Parse(path.to_path_buf(), err))
+    }
+}
+
+impl fmt::Display for RuleSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, rule) in self.rules.values().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            rule.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let premises: Vec<_> = self.premises.iter().map(Expr::to_string).collect();
+        let conclusion = self.conclusion.to_string();
+
+        writeln!(f, "{} {}", HEADER, self.name)?;
+
+        if !premises.is_empty() {
+            let lines = premises.iter().chain(iter::once(&conclusion));
+            let width = lines.map(|line| line.chars().count()).max().unwrap_or(0);
+
+            for premise in &premises {
+                writeln!(f, "    {}", premise)?;
+            }
+            writeln!(f, "    {}", "-".repeat(cmp::max(width, 3)))?;
+        }
+
+        writeln!(f, "    {}", conclusion)
+    }
+}
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, ParseError),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::Io(path, err) => write!(f, "{}: {}", path.display(), err),
+            LoadError::Parse(path, err) => write!(f, "{}:{}", path.display(), err),
+        }
+    }
+}
+
+impl Error for LoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LoadError::Io(_, err) => Some(err),
+            LoadError::Parse(_, err) => Some(err),
+        }
+    }
+}
+
+fn parse_rules(source: &str) -> Result<Vec<(String, Expr, Vec<Expr>)>, ParseError> {
+    let mut drafts: Vec<Draft> = Vec::new();
+
+    for (i, text) in source.lines().enumerate() {
+        let line = Line::new(i + 1, text);
+        let words = line.words();
+
+        match words.first() {
+            None => continue,
+            Some(&(_, _, word)) if word == HEADER => {
+                let draft = Draft::new(&line, &words)?;
+
+                if drafts.iter().any(|other| other.name == draft.name) {
+                    let message = format!("duplicate rule '{}'", draft.name);
+                    return Err(line.error(draft.header.1, message));
+                }
+                drafts.push(draft);
+            }
+            Some(&(_, column, _)) => match drafts.last_mut() {
+                Some(draft) => draft.add(&line, &words)?,
+                None => return Err(line.error(column, "expected 'rule'")),
+            },
+        }
+    }
+
+    drafts.iter().map(Draft::finish).collect()
+}
+
+struct Line<'a> {
+    number: usize,
+    text: &'a str,
+}
+
+impl Line<'_> {
+    fn new(number: usize, text: &str) -> Line<'_> {
+        let comment = unquoted(text).find(|&(_, c, bare)| bare && c == COMMENT);
+
+        let text = match comment {
+            Some((offset, _, _)) => &text[..offset],
+            None => text,
+        };
+
+        Line { number, text }
+    }
+
+    fn words(&self) -> Vec<(usize, usize, &str)> {
+        let mut words = Vec::new();
+        let mut start = None;
+
+        for (column, (offset, c, bare)) in unquoted(self.text).enumerate() {
+            match (start, bare && c.is_whitespace()) {
+                (None, false) => start = Some((offset, column + 1)),
+                (Some((first, col)), true) => {
+                    words.push((first, col, &self.text[first..offset]));
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+
+        if let Some((first, col)) = start {
+            words.push((first, col, &self.text[first..]));
+        }
+
+        words
+    }
+
+    fn is_divider(&self) -> bool {
+        let text = self.text.trim();
+        text.chars().count() >= 3 && text.chars().all(|c| c == '-' || c == '\u{2500}')
+    }
+
+    fn end(&self) -> usize {
+        self.text.trim_end().chars().count() + 1
+    }
+
+    fn column(&self, offset: usize) -> usize {
+        self.text[..offset].chars().count() + 1
+    }
+
+    fn error<S: Into<String>>(&self, column: usize, message: S) -> ParseError {
+        ParseError {
+            line: self.number,
+            column,
+            message: message.into(),
+        }
+    }
+}
+
+fn unquoted(text: &str) -> impl Iterator<Item = (usize, char, bool)> + '_ {
+    let (mut quoted, mut escaped) = (false, false);
+
+    text.char_indices().map(move |(offset, c)| {
+        let bare = !quoted && !escaped && c != QUOTE;
+
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == QUOTE {
+            quoted = !quoted;
+        }
+
+        (offset, c, bare)
+    })
+}
+
+enum Form {
+    Axiom,
+    Divider(usize, usize),
+    Turnstile(usize, usize),
+}
+
+struct Draft {
+    name: String,
+    header: (usize, usize),
+    form: Form,
+    above: Vec<(usize, usize, Expr)>,
+    below: Vec<(usize, usize, Expr)>,
+}
+
+impl Draft {
+    fn new(line: &Line, words: &[(usize, usize, &str)]) -> Result<Draft, ParseError> {
+        match words {
+            [_] => Err(line.error(line.end(), "expected a rule name")),
+            [_, (_, column, name)] => Ok(Draft {
+                name: String::from(*name),
+                header: (line.number, *column),
+                form: Form::Axiom,
+                above: Vec::new(),
+                below: Vec::new(),
+            }),
+            _ => Err(line.error(words[2].1, "expected end of line")),
+        }
+    }
+
+    fn add(&mut self, line: &Line, words: &[(usize, usize, &str)]) -> Result<(), ParseError> {
+        let column = words[0].1;
+
+        if line.is_divider() {
+            if let Form::Axiom = self.form {
+                self.form = Form::Divider(line.number, column);
+                return Ok(());
+            }
+            return Err(line.error(column, "unexpected divider"));
+        }
+
+        let turnstile = words.iter().find(|(_, _, word)| *word == TURNSTILE);
+
+        if let Some(&(offset, column, _)) = turnstile {
+            if let Form::Axiom = self.form {
+                self.form = Form::Turnstile(line.number, column);
+            } else {
+                return Err(line.error(column, format!("unexpected '{}'", TURNSTILE)));
+            }
+
+            let rest = offset + TURNSTILE.len();
+            parse_items(line, 0, &line.text[..offset], &mut self.above)?;
+            parse_items(line, rest, &line.text[rest..], &mut self.below)
+        } else if let Form::Axiom = self.form {
+            parse_items(line, 0, line.text, &mut self.above)
+        } else {
+            parse_items(line, 0, line.text, &mut self.below)
+        }
+    }
+
+    fn finish(&self) -> Result<(String, Expr, Vec<Expr>), ParseError> {
+        let (conclusions, premises, position) = match self.form {
+            Form::Axiom => (&self.above, &self.below, self.header),
+            Form::Divider(line, column) => (&self.below, &self.above, (line, column)),
+            Form::Turnstile(line, column) => (&self.above, &self.below, (line, column)),
+        };
+
+        match conclusions.as_slice() {
+            [] => Err(ParseError {
+                line: position.0,
+                column: position.1,
+                message: format!("expected a conclusion for rule '{}'", self.name),
+            }),
+            [(_, _, conclusion)] => {
+                let premises = premises.iter().map(|(_, _, expr)| expr.clone());
+                Ok((self.name.clone(), conclusion.clone(), premises.collect()))
+            }
+            [_, (line, column, _), ..] => Err(ParseError {
+                line: *line,
+                column: *column,
+                message: format!("rule '{}' has more than one conclusion", self.name),
+            }),
+        }
+    }
+}
+
+fn parse_items(
+    line: &Line,
+    offset: usize,
+    text: &str,
+    items: &mut Vec<(usize, usize, Expr)>,
+) -> Result<(), ParseError> {
+    if text.trim().is_empty() {
+        return Ok(());
+    }
+
+    let mut parser = Parser::at(text, line.number, line.column(offset));
+
+    loop {
+        let (line, column) = parser.position();
+        items.push((line, column, parser.sequence()?));
+
+        if !parser.skip(',') {
+            return parser.finish();
+        }
+        if parser.is_done() {
+            return Ok(());
+        }
+    }
+}
@@ -1,16 +1,53 @@
+mod builtin;
+mod check;
+mod index;
+mod limits;
+mod load;
+mod search;
+mod solution;
+mod table;
 mod tests;
 
+pub use self::check::CheckError;
+pub use self::limits::{Derivation, Limits, Status};
+pub use self::load::LoadError;
+pub use self::search::SearchStrategy;
+pub use self::solution::Solution;
+
 use crate::expr::*;
-use crate::iter::{BoxIter, Flatten, Interleave};
+use crate::iter::BoxIter;
 use crate::proof::Proof;
 use crate::state::State;
 use im::vector::Vector;
+use index::Index;
 use indexmap::map::IndexMap;
+use search::{Deepening, Search};
+use std::cmp;
+use std::iter;
 use std::rc::Rc;
 
-#[derive(Default)]
+const NOT: &str = "not";
+
 pub struct RuleSet {
     rules: IndexMap<String, Rule>,
+    index: Index,
+    indexing: bool,
+    tabled: Vec<Expr>,
+    occurs_check: bool,
+    negation_depth: usize,
+}
+
+impl Default for RuleSet {
+    fn default() -> RuleSet {
+        RuleSet {
+            rules: IndexMap::new(),
+            index: Index::default(),
+            indexing: true,
+            tabled: Vec::new(),
+            occurs_check: true,
+            negation_depth: 16,
+        }
+    }
 }
 
 impl RuleSet {
@@ -18,6 +55,18 @@ impl RuleSet {
         RuleSet::default()
     }
 
+    pub fn set_occurs_check(&mut self, enabled: bool) {
+        self.occurs_check = enabled;
+    }
+
+    pub fn set_negation_depth(&mut self, depth: usize) {
+        self.negation_depth = depth;
+    }
+
+    pub fn set_indexing(&mut self, enabled: bool) {
+        self.indexing = enabled;
+    }
+
     pub fn insert(&mut self, name: &str, conclusion: &Expr, premises: &[Expr]) {
         let rule = Rule {
             name: String::from(name),
@@ -25,22 +74,106 @@ impl RuleSet {
             conclusion: conclusion.clone(),
         };
 
-        self.rules.insert(String::from(name), rule);
+        let (position, replaced) = self.rules.insert_full(String::from(name), rule);
+
+        if replaced.is_none() {
+            self.index.insert(position, conclusion);
+        } else {
+            self.index = Index::default();
+
+            for (position, rule) in self.rules.values().enumerate() {
+                self.index.insert(position, &rule.conclusion);
+            }
+        }
+    }
+
+    pub fn derive(&self, target: &Expr) -> Stream<'_, Rc<Proof>> {
+        self.derive_with(target, SearchStrategy::default())
     }
 
-    pub fn derive(&self, target: &Expr) -> Interleave<(State, Rc<Proof>)> {
-        self.derive_in_state(&State::new(), (0, target))
+    pub fn derive_with(&self, target: &Expr, strategy: SearchStrategy) -> Stream<'_, Rc<Proof>> {
+        let state = self.initial_state();
+
+        match strategy {
+            SearchStrategy::IterativeDeepening => Box::new(Deepening::new(self, state, target)),
+            _ => self.derive_in_state(&Search::new(strategy), &state, (0, target), None),
+        }
+    }
+
+    pub fn derive_with_limits(&self, target: &Expr, limits: Limits) -> Derivation<'_> {
+        Derivation::new(self, target, limits)
+    }
+
+    fn initial_state(&self) -> State {
+        let mut state = State::new();
+        state.set_occurs_check(self.occurs_check);
+        state
     }
 
     fn derive_in_state(
         &self,
+        search: &Search,
         state: &State,
         target: (usize, &Expr),
-    ) -> Interleave<(State, Rc<Proof>)> {
-        let rules = self.rules.values();
-        let streams = rules.map(|rule| rule.match_target(self, state, target));
+        depth: Option<usize>,
+    ) -> Stream<'_, Rc<Proof>> {
+        if depth == Some(0) {
+            if self.can_match(state, target) {
+                search.prune();
+            }
+            return Box::new(iter::empty());
+        }
+
+        let depth = depth.map(|d| d - 1);
+
+        if let Some((name, states)) = builtin::evaluate(state, target) {
+            if !search.step() {
+                return Box::new(iter::empty());
+            }
 
-        Interleave::new(streams)
+            let proofs = states.into_iter().map(|state| {
+                let proof = Proof::new(name, &state, Vector::new(), target);
+                (state, Rc::new(proof))
+            });
+            return Box::new(proofs.collect::<Vec<_>>().into_iter());
+        }
+
+        if let Some(answers) = self.derive_tabled(search, state, target, depth) {
+            return answers;
+        }
+
+        let rules = self.candidates(state, target).into_iter();
+        let streams = rules.map(|rule| rule.match_target(self, search, state, target, depth));
+
+        search.alternatives(streams)
+    }
+
+    fn candidates(&self, state: &State, target: (usize, &Expr)) -> Vec<&Rule> {
+        let positions = if self.indexing {
+            self.index.candidates(state, target)
+        } else {
+            None
+        };
+
+        match positions {
+            Some(positions) => {
+                let rules = positions.iter().filter_map(|&i| self.rules.get_index(i));
+                rules.map(|(_, rule)| rule).collect()
+            }
+            None => self.rules.values().collect(),
+        }
+    }
+
+    fn can_match(&self, state: &State, target: (usize, &Expr)) -> bool {
+        if let Some((_, states)) = builtin::evaluate(state, target) {
+            return !states.is_empty();
+        }
+
+        self.candidates(state, target).into_iter().any(|rule| {
+            let mut state = state.clone();
+            let scope = state.new_scope();
+            state.unify(target, (scope, &rule.conclusion)).is_some()
+        })
     }
 }
 
@@ -56,23 +189,64 @@ impl Rule {
     fn match_target<'a>(
         &'a self,
         rule_set: &'a RuleSet,
+        search: &Search,
         state: &State,
         target: (usize, &Expr),
+        depth: Option<usize>,
     ) -> Stream<'a, Rc<Proof>> {
-        let scope = state.scope();
+        let mut state = state.clone();
+        let scope = state.new_scope();
         let conclusion = (scope, &self.conclusion);
         let premises = self.premises.iter().map(|premise| (scope, premise));
 
+        if !search.step() {
+            return Box::new(iter::empty());
+        }
+
         let state_or_none = state.unify(target, conclusion).into_iter();
         let init = Box::new(state_or_none.map(|state| (state, Vector::new())));
 
         let states: Stream<Vector<_>> = premises.fold(init, |states, premise| {
+            if let Some(goal) = negated(premise.1) {
+                if depth == Some(0) {
+                    search.prune();
+                    return Box::new(iter::empty());
+                }
+
+                let limit = rule_set.negation_depth;
+                let outer_cut = depth.map_or(false, |d| d < limit);
+                let depth = Some(depth.map_or(limit, |d| cmp::min(d, limit)));
+                let search = search.clone();
+
+                return Box::new(states.filter_map(move |(state, proofs)| {
+                    let goal = (scope, &goal);
+                    let nested = search.negated();
+                    let mut proof_states = rule_set.derive_in_state(&nested, &state, goal, depth);
+
+                    if proof_states.next().is_some() || nested.is_halted() {
+                        return None;
+                    }
+
+                    if nested.is_pruned() {
+                        if outer_cut {
+                            search.prune();
+                        }
+                        return None;
+                    }
+
+                    let proof = Rc::new(Proof::new(NOT, &state, Vector::new(), premise));
+                    Some((state, concat(&proofs, &proof)))
+                }));
+            }
+
+            let inner = search.clone();
+
             let streams = states.map(move |(state, proofs)| {
-                let proof_states = rule_set.derive_in_state(&state, premise);
+                let proof_states = rule_set.derive_in_state(&inner, &state, premise, depth);
                 proof_states.map(move |(state, proof)| (state, concat(&proofs, &proof)))
             });
 
-            Box::new(Flatten::new(streams))
+            search.conjunction(streams)
         });
 
         Box::new(states.map(move |(state, proofs)| {
@@ -82,6 +256,19 @@ impl Rule {
     }
 }
 
+fn negated(premise: &Expr) -> Option<Expr> {
+    if let Expr::Seq(seq) = premise {
+        match seq.0.as_slice() {
+            [Expr::Wrd(word), goal] if word.0 == NOT => return Some(goal.clone()),
+            [Expr::Wrd(word), goal @ ..] if word.0 == NOT && !goal.is_empty() => {
+                return Some(Expr::Seq(Rc::new(Sequence(goal.to_vec()))));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
 fn concat<T: Clone>(list: &Vector<T>, item: &T) -> Vector<T> {
     let mut list = list.clone();
     list.push_back(item.clone());
@@ -0,0 +1,172 @@
+use super::table::Tables;
+use super::RuleSet;
+use crate::expr::Expr;
+use crate::iter::{BoxIter, Flatten, Interleave};
+use crate::proof::Proof;
+use crate::state::State;
+use std::cell::{Cell, RefCell};
+use std::iter;
+use std::rc::Rc;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SearchStrategy {
+    DepthFirst,
+    Interleave,
+    IterativeDeepening,
+}
+
+impl Default for SearchStrategy {
+    fn default() -> SearchStrategy {
+        SearchStrategy::Interleave
+    }
+}
+
+#[derive(Clone)]
+pub(super) struct Search {
+    strategy: SearchStrategy,
+    pruned: Rc<Cell<bool>>,
+    negated: bool,
+    steps: Rc<Cell<usize>>,
+    max_steps: Option<usize>,
+    tables: Rc<RefCell<Tables>>,
+}
+
+impl Search {
+    pub fn new(strategy: SearchStrategy) -> Search {
+        Search::limited(strategy, None)
+    }
+
+    pub fn limited(strategy: SearchStrategy, max_steps: Option<usize>) -> Search {
+        Search {
+            strategy,
+            pruned: Rc::new(Cell::new(false)),
+            negated: false,
+            steps: Rc::new(Cell::new(0)),
+            max_steps,
+            tables: Rc::new(RefCell::new(Tables::default())),
+        }
+    }
+
+    pub fn nested(&self) -> Search {
+        Search {
+            pruned: Rc::new(Cell::new(false)),
+            ..self.clone()
+        }
+    }
+
+    pub fn negated(&self) -> Search {
+        Search {
+            negated: true,
+            ..self.nested()
+        }
+    }
+
+    pub fn stratified(&self) -> Search {
+        Search {
+            negated: false,
+            ..self.nested()
+        }
+    }
+
+    pub fn is_negated(&self) -> bool {
+        self.negated
+    }
+
+    pub fn prune(&self) {
+        self.pruned.set(true);
+    }
+
+    pub fn is_pruned(&self) -> bool {
+        self.pruned.get()
+    }
+
+    pub fn step(&self) -> bool {
+        let steps = self.steps.get() + 1;
+        self.steps.set(steps);
+        self.max_steps.map_or(true, |max| steps <= max)
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.max_steps.map_or(false, |max| self.steps.get() > max)
+    }
+
+    pub fn tables(&self) -> &RefCell<Tables> {
+        &self.tables
+    }
+
+    pub fn alternatives<'a, I, T>(&self, streams: I) -> BoxIter<'a, T>
+    where
+        I: IntoIterator<Item = BoxIter<'a, T>>,
+        T: 'a,
+    {
+        let streams: Vec<_> = streams.into_iter().collect();
+
+        match self.strategy {
+            SearchStrategy::Interleave => Box::new(Interleave::new(streams)),
+            _ => Box::new(streams.into_iter().flatten()),
+        }
+    }
+
+    pub fn conjunction<'a, I, J, T>(&self, streams: I) -> BoxIter<'a, T>
+    where
+        I: Iterator<Item = J> + 'a,
+        J: IntoIterator<Item = T> + 'a,
+        T: 'a,
+    {
+        match self.strategy {
+            SearchStrategy::Interleave => Box::new(Flatten::new(streams)),
+            _ => Box::new(streams.flatten()),
+        }
+    }
+}
+
+pub(super) struct Deepening<'a> {
+    rule_set: &'a RuleSet,
+    search: Search,
+    state: State,
+    target: Expr,
+    depth: usize,
+    stream: BoxIter<'a, (State, Rc<Proof>)>,
+}
+
+impl<'a> Deepening<'a> {
+    pub fn new(rule_set: &'a RuleSet, state: State, target: &Expr) -> Deepening<'a> {
+        let search = Search::new(SearchStrategy::DepthFirst);
+        search.prune();
+
+        Deepening {
+            rule_set,
+            search,
+            state,
+            target: target.clone(),
+            depth: 0,
+            stream: Box::new(iter::empty()),
+        }
+    }
+}
+
+impl Iterator for Deepening<'_> {
+    type Item = (State, Rc<Proof>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            for (state, proof) in &mut self.stream {
+                if proof.depth() == self.depth {
+                    return Some((state, proof));
+                }
+            }
+
+            if !self.search.pruned.replace(false) {
+                return None;
+            }
+
+            self.depth += 1;
+
+            let target = (0, &self.target);
+            let depth = Some(self.depth);
+            self.stream = self
+                .rule_set
+                .derive_in_state(&self.search, &self.state, target, depth);
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use super::RuleSet;
+use crate::expr::*;
+use crate::proof::Proof;
+use indexmap::map::IndexMap;
+use std::fmt;
+use std::rc::Rc;
+
+#[derive(Debug)]
+pub struct Solution {
+    bindings: IndexMap<String, Expr>,
+    proof: Rc<Proof>,
+}
+
+impl RuleSet {
+    pub fn query<'a>(&'a self, target: &Expr) -> impl Iterator<Item = Solution> + 'a {
+        let vars = target.variables();
+        let names: Vec<_> = vars.iter().map(|var| var.0.clone()).collect();
+
+        let vars = vars.into_iter().map(Expr::Var);
+        let query = Expr::Seq(Rc::new(Sequence(vars.collect())));
+
+        self.derive(target).map(move |(state, proof)| {
+            let values = match state.reify(&query) {
+                Expr::Seq(seq) => seq.0.clone(),
+                _ => Vec::new(),
+            };
+
+            Solution {
+                bindings: names.iter().cloned().zip(values).collect(),
+                proof,
+            }
+        })
+    }
+}
+
+impl Solution {
+    pub fn get(&self, name: &str) -> Option<&Expr> {
+        self.bindings.get(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Expr)> {
+        self.bindings
+            .iter()
+            .map(|(name, value)| (name.as_str(), value))
+    }
+
+    pub fn len(&self) -> usize {
+        self.bindings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bindings.is_empty()
+    }
+
+    pub fn proof(&self) -> &Rc<Proof> {
+        &self.proof
+    }
+}
+
+impl fmt::Display for Solution {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "true");
+        }
+
+        for (i, (name, value)) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "${} = {}", name, value)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,286 @@
+use super::search::Search;
+use super::{RuleSet, Stream};
+use crate::expr::Expr;
+use crate::proof::{Proof, ProofTree};
+use crate::state::State;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+#[derive(Default)]
+pub(super) struct Tables {
+    entries: HashMap<String, Table>,
+    running: bool,
+    pass: usize,
+    changed: bool,
+    cut: bool,
+    stratum: usize,
+}
+
+#[derive(Default)]
+struct Table {
+    answers: Vec<Answer>,
+    keys: HashSet<String>,
+    pass: usize,
+    depth: Option<usize>,
+    cut: bool,
+    stratum: usize,
+    complete: bool,
+}
+
+// Answers are stored with their variables named canonically, together with
+// any `!=` constraints still pending on them and the proof that found them,
+// so that they can be replayed into the state of each caller.
+#[derive(Clone)]
+struct Answer {
+    goal: Expr,
+    constraints: Vec<(Expr, Expr)>,
+    proof: ProofTree,
+    depth: usize,
+}
+
+enum Role {
+    Leader,
+    Member,
+    Stratum,
+}
+
+impl RuleSet {
+    pub fn table(&mut self, pattern: &Expr) {
+        self.tabled.push(pattern.clone());
+    }
+
+    pub(super) fn derive_tabled<'a>(
+        &'a self,
+        search: &Search,
+        state: &State,
+        target: (usize, &Expr),
+        depth: Option<usize>,
+    ) -> Option<Stream<'a, Rc<Proof>>> {
+        if self.tabled.is_empty() {
+            return None;
+        }
+
+        let goal = state.reify_scoped(target.1, target.0);
+
+        let is_tabled = self.tabled.iter().any(|pattern| {
+            let state = State::new();
+            state.unify((0, &goal), (1, pattern)).is_some()
+        });
+
+        if !is_tabled {
+            return None;
+        }
+
+        let key = goal.to_string();
+        self.fill_table(search, &key, &goal, depth);
+
+        let (answers, cut) = {
+            let tables = search.tables().borrow();
+            let table = &tables.entries[&key];
+            (table.answers.clone(), table.complete && table.cut)
+        };
+
+        if cut {
+            search.prune();
+        }
+
+        let states = answers.into_iter().filter_map(|answer| {
+            if depth.map_or(false, |d| answer.depth > d + 1) {
+                search.prune();
+                return None;
+            }
+
+            let mut state = state.clone();
+            let scope = state.new_scope();
+            let state = state.unify(target, (scope, &answer.goal))?;
+
+            let constraints = answer.constraints.iter();
+            let state = constraints.fold(Some(state), |state, (x, y)| {
+                state?.disunify((scope, x), (scope, y))
+            })?;
+
+            let proof = Proof::from_tree(&answer.proof, &state, scope);
+            Some((state, Rc::new(proof)))
+        });
+
+        Some(Box::new(states.collect::<Vec<_>>().into_iter()))
+    }
+
+    fn fill_table(&self, search: &Search, key: &str, goal: &Expr, depth: Option<usize>) {
+        let role = {
+            let mut tables = search.tables().borrow_mut();
+            let (pass, stratum) = (tables.pass, tables.stratum);
+
+            if let Some(table) = tables.entries.get_mut(key) {
+                let covered = covers(table.depth, depth);
+
+                if table.complete && (covered || !table.cut) {
+                    return;
+                }
+                if !table.complete && (search.is_negated() || table.stratum < stratum) {
+                    search.prune();
+                    return;
+                }
+                if table.pass == pass && covered {
+                    return;
+                }
+                if table.complete {
+                    table.complete = false;
+                    table.cut = false;
+                }
+            }
+
+            if search.is_negated() {
+                Role::Stratum
+            } else if !std::mem::replace(&mut tables.running, true) {
+                Role::Leader
+            } else {
+                Role::Member
+            }
+        };
+
+        match role {
+            Role::Leader => self.complete_tables(search, key, goal, depth),
+            Role::Member => self.evaluate_goal(search, key, goal, depth),
+            Role::Stratum => self.complete_stratum(search, key, goal, depth),
+        }
+    }
+
+    // A table read through a negated premise must be complete before the
+    // negation can be decided, so it is run to a fixpoint of its own, apart
+    // from any tables still being filled. If that fixpoint needs one of
+    // those unfinished tables, the rules are not stratified and the branch
+    // is cut off instead.
+    fn complete_stratum(&self, search: &Search, key: &str, goal: &Expr, depth: Option<usize>) {
+        let tables = search.tables();
+
+        let saved = {
+            let mut tables = tables.borrow_mut();
+            let saved = (tables.running, tables.pass, tables.changed, tables.cut);
+            tables.running = true;
+            tables.stratum += 1;
+            saved
+        };
+
+        self.complete_tables(&search.stratified(), key, goal, depth);
+
+        let mut tables = tables.borrow_mut();
+        let (running, pass, changed, cut) = saved;
+
+        tables.running = running;
+        tables.pass = pass;
+        tables.changed = changed;
+        tables.cut = cut;
+        tables.stratum -= 1;
+    }
+
+    fn complete_tables(&self, search: &Search, key: &str, goal: &Expr, depth: Option<usize>) {
+        let tables = search.tables();
+
+        let start = {
+            let mut tables = tables.borrow_mut();
+            tables.cut = false;
+            tables.pass
+        };
+
+        loop {
+            {
+                let mut tables = tables.borrow_mut();
+                tables.pass += 1;
+                tables.changed = false;
+            }
+
+            self.evaluate_goal(search, key, goal, depth);
+
+            if !tables.borrow().changed {
+                break;
+            }
+        }
+
+        let mut tables = tables.borrow_mut();
+        let cut = tables.cut;
+        tables.running = false;
+
+        for table in tables.entries.values_mut() {
+            if !table.complete && table.pass > start {
+                table.cut = table.cut || cut;
+                table.complete = true;
+            }
+        }
+    }
+
+    fn evaluate_goal(&self, search: &Search, key: &str, goal: &Expr, depth: Option<usize>) {
+        let tables = search.tables();
+
+        {
+            let mut tables = tables.borrow_mut();
+            let (pass, stratum) = (tables.pass, tables.stratum);
+            let table = tables.entries.entry(String::from(key)).or_default();
+            table.pass = pass;
+            table.stratum = stratum;
+            table.depth = depth;
+        }
+
+        let state = self.initial_state();
+        let search = search.nested();
+
+        let rules = self.candidates(&state, (0, goal)).into_iter();
+
+        let streams = rules.map(|rule| {
+            let states = rule.match_target(self, &search, &state, (0, goal), depth);
+            states.collect::<Vec<_>>()
+        });
+        let results: Vec<_> = streams.flatten().collect();
+
+        let mut tables = tables.borrow_mut();
+        let cut = search.is_pruned();
+
+        tables.cut = tables.cut || cut;
+        tables.entries.get_mut(key).unwrap().cut = cut;
+
+        for (state, proof) in results {
+            let answer = reify_answer(&state, goal, &proof);
+            let table = tables.entries.get_mut(key).unwrap();
+
+            if table.keys.insert(answer_key(&answer)) {
+                table.answers.push(answer);
+                tables.changed = true;
+            }
+        }
+    }
+}
+
+fn reify_answer(state: &State, goal: &Expr, proof: &Proof) -> Answer {
+    let mut names = Vec::new();
+    let goal = state.rename((0, goal), &mut names);
+
+    let constraints = state.constraints().map(|((xs, x), (ys, y))| {
+        let x = state.rename((*xs, x), &mut names);
+        (x, state.rename((*ys, y), &mut names))
+    });
+    let constraints = constraints.collect();
+
+    Answer {
+        goal,
+        constraints,
+        proof: proof.reify_tree(state, &mut names),
+        depth: proof.depth(),
+    }
+}
+
+fn answer_key(answer: &Answer) -> String {
+    let mut key = answer.goal.to_string();
+
+    for (x, y) in &answer.constraints {
+        key.push_str(&format!(", {} != {}", x, y));
+    }
+    key
+}
+
+fn covers(filled: Option<usize>, wanted: Option<usize>) -> bool {
+    match (filled, wanted) {
+        (None, _) => true,
+        (Some(_), None) => false,
+        (Some(filled), Some(wanted)) => filled >= wanted,
+    }
+}
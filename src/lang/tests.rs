@@ -1,6 +1,7 @@
 #![cfg(test)]
 
 use super::*;
+use crate::proof::ProofTree;
 use crate::*;
 
 fn append_rules() -> RuleSet {
@@ -250,3 +251,1491 @@ fn transitive_relation_with_infinite_derivations() {
 
     assert_eq!(results, vec![expr!(wrd(a)), expr!(wrd(a)), expr!(wrd(b))]);
 }
+
+fn load(source: &str) -> Result<RuleSet, ParseError> {
+    let mut rules = RuleSet::new();
+    rules.load_str(source)?;
+    Ok(rules)
+}
+
+fn load_error(source: &str) -> ParseError {
+    RuleSet::new().load_str(source).unwrap_err()
+}
+
+fn answers(rules: &RuleSet, query: &str) -> Vec<Expr> {
+    let query = parse(query).unwrap();
+
+    rules
+        .derive(&query)
+        .map(|(s, _)| s.resolve(&query))
+        .collect()
+}
+
+#[test]
+fn load_rules_with_divider() {
+    let rules = load(
+        "
+        rule append-0
+            nil + $list = $list
+
+        rule append-N
+                    $tail + $list = $rest
+            --------------------------------------
+            ($head $tail) + $list = ($head $rest)
+        ",
+    )
+    .unwrap();
+
+    assert_eq!(
+        answers(&rules, "(a (b nil)) + (c nil) = $answer"),
+        vec![parse("(a (b nil)) + (c nil) = (a (b (c nil)))").unwrap()]
+    );
+}
+
+#[test]
+fn load_rules_with_turnstile() {
+    let rules = load(
+        "
+        # Lists are typed inductively.
+        rule type-0
+            λ[] : List
+
+        rule type-N
+            λ[$head | $tail] : List :- $tail : List   # one premise
+
+        rule type-append
+            ($a + $b) : List :-
+                $a : List,
+                $b : List
+        ",
+    )
+    .unwrap();
+
+    assert_eq!(
+        answers(&rules, "(λ[a, b] + λ[c]) : $type"),
+        vec![parse("(λ[a, b] + λ[c]) : List").unwrap()]
+    );
+}
+
+#[test]
+fn load_premises_from_lines_and_commas() {
+    let rules = load(
+        "
+        rule both
+            a $x, b $x
+            c $x
+            ---------
+            all $x
+
+        rule a
+            a k
+        rule b
+            b k
+        rule c
+            c k
+        ",
+    )
+    .unwrap();
+
+    assert_eq!(answers(&rules, "all $y"), vec![parse("all k").unwrap()]);
+}
+
+#[test]
+fn load_rules_from_file() {
+    let mut rules = RuleSet::new();
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/examples/append.rules");
+    rules.load_file(path).unwrap();
+
+    assert_eq!(
+        answers(&rules, "rev λ[a, b, c] = $answer"),
+        vec![parse("rev λ[a, b, c] = λ[c, b, a]").unwrap()]
+    );
+}
+
+#[test]
+fn report_missing_rule_files() {
+    let mut rules = RuleSet::new();
+    let error = rules.load_file("no/such/file.rules").unwrap_err();
+
+    assert!(matches!(error, LoadError::Io(_, _)));
+    assert!(error.to_string().starts_with("no/such/file.rules: "));
+}
+
+#[test]
+fn reject_premises_without_rule() {
+    let error = load_error("\n  a b c\n");
+    assert_eq!((error.line, error.column), (2, 3));
+    assert_eq!(error.message, "expected 'rule'");
+}
+
+#[test]
+fn reject_rule_without_name() {
+    let error = load_error("rule   \n  a\n");
+    assert_eq!((error.line, error.column), (1, 5));
+    assert_eq!(error.message, "expected a rule name");
+}
+
+#[test]
+fn reject_duplicate_rules() {
+    let error = load_error("rule a\n  x\nrule a\n  y\n");
+    assert_eq!((error.line, error.column), (3, 6));
+    assert_eq!(error.message, "duplicate rule 'a'");
+}
+
+#[test]
+fn reject_rule_without_conclusion() {
+    let error = load_error("rule a\n  x\n  ---\n");
+    assert_eq!((error.line, error.column), (3, 3));
+    assert_eq!(error.message, "expected a conclusion for rule 'a'");
+}
+
+#[test]
+fn reject_rule_with_many_conclusions() {
+    let error = load_error("rule a\n  x\n  y\n");
+    assert_eq!((error.line, error.column), (3, 3));
+    assert_eq!(error.message, "rule 'a' has more than one conclusion");
+}
+
+#[test]
+fn reject_second_divider() {
+    let error = load_error("rule a\n  x\n  ---\n  y :- z\n");
+    assert_eq!((error.line, error.column), (4, 5));
+    assert_eq!(error.message, "unexpected ':-'");
+}
+
+#[test]
+fn reject_invalid_expressions_with_position() {
+    let error = load_error("rule a\n  x\n  ---\n  f (x  # unclosed\n");
+    assert_eq!((error.line, error.column), (4, 9));
+    assert_eq!(error.message, "expected ')', found end of input");
+}
+
+#[test]
+fn reject_expressions_after_turnstile_with_position() {
+    let error = load_error("rule a\n  x :- y, ]\n");
+    assert_eq!((error.line, error.column), (2, 11));
+    assert_eq!(error.message, "expected an expression, found ']'");
+}
+
+#[test]
+fn display_rules_in_file_syntax() {
+    let mut rules = RuleSet::new();
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/examples/append.rules");
+    rules.load_file(path).unwrap();
+
+    let text = rules.to_string();
+    assert!(text.starts_with("rule append-0\n    λ[] + $list = $list\n\nrule append-N\n"));
+
+    let reloaded = load(&text).unwrap();
+    assert_eq!(reloaded.to_string(), text);
+}
+
+#[test]
+fn reject_cyclic_solutions() {
+    let mut rules = RuleSet::new();
+    rules.insert("same", &expr!(seq(var(x), wrd(same), var(x))), &[]);
+
+    let query = expr!(seq(var(y), wrd(same), seq(wrd(f), var(y))));
+    assert_eq!(rules.derive(&query).count(), 0);
+
+    rules.set_occurs_check(false);
+    assert_eq!(rules.derive(&query).count(), 1);
+}
+
+#[test]
+fn rename_variables_after_variable_free_rules() {
+    let mut rules = RuleSet::new();
+
+    //  pair $v b
+    //  ---------
+    //     top
+
+    rules.insert(
+        "top",
+        &expr!(wrd(top)),
+        &[expr!(seq(wrd(pair), var(v), wrd(b)))],
+    );
+
+    //  pair a $v
+
+    rules.insert("pair", &expr!(seq(wrd(pair), wrd(a), var(v))), &[]);
+
+    assert_eq!(rules.derive(&expr!(wrd(top))).count(), 1);
+}
+
+#[test]
+fn rename_variables_in_repeated_applications() {
+    let mut rules = RuleSet::new();
+
+    //  item $x     item $y
+    //  -------------------
+    //     pair ($x $y)
+
+    rules.insert(
+        "pair",
+        &expr!(seq(wrd(pair), seq(var(x), var(y)))),
+        &[expr!(seq(wrd(item), var(x))), expr!(seq(wrd(item), var(y)))],
+    );
+
+    //  item a
+    //  item b
+
+    rules.insert("item-a", &expr!(seq(wrd(item), wrd(a))), &[]);
+    rules.insert("item-b", &expr!(seq(wrd(item), wrd(b))), &[]);
+
+    let query = expr!(seq(wrd(pair), var(p)));
+
+    let mut results: Vec<_> = rules
+        .derive(&query)
+        .map(|(s, _)| s.resolve(&expr!(var(p))).to_string())
+        .collect();
+
+    results.sort();
+    assert_eq!(results, vec!["a a", "a b", "b a", "b b"]);
+}
+
+fn context_rules() -> RuleSet {
+    let mut rules = RuleSet::new();
+
+    //  ctx[$x : $t | $rest] has $x : $t
+
+    rules.insert(
+        "lookup-here",
+        &expr!(seq(
+            lst(ctx, [seq(var(x), wrd(:), var(t)) | var(rest)]),
+            wrd(has),
+            var(x),
+            wrd(:),
+            var(t)
+        )),
+        &[],
+    );
+
+    //  $x != $y        $rest has $x : $t
+    //  ---------------------------------
+    //   ctx[$y : $s | $rest] has $x : $t
+
+    rules.insert(
+        "lookup-there",
+        &expr!(seq(
+            lst(ctx, [seq(var(y), wrd(:), var(s)) | var(rest)]),
+            wrd(has),
+            var(x),
+            wrd(:),
+            var(t)
+        )),
+        &[
+            expr!(seq(var(x), wrd(!=), var(y))),
+            expr!(seq(var(rest), wrd(has), var(x), wrd(:), var(t))),
+        ],
+    );
+
+    rules
+}
+
+#[test]
+fn prune_derivations_violating_disequality() {
+    // ctx[x : A, y : B, x : C] has x : ?
+
+    let query = expr!(seq(
+        lst(
+            ctx,
+            [
+                seq(wrd(x), wrd(:), wrd(A)),
+                seq(wrd(y), wrd(:), wrd(B)),
+                seq(wrd(x), wrd(:), wrd(C)),
+            ]
+        ),
+        wrd(has),
+        wrd(x),
+        wrd(:),
+        var(type)
+    ));
+
+    let results: Vec<_> = context_rules()
+        .derive(&query)
+        .map(|(s, _)| s.resolve(&expr!(var(type))))
+        .collect();
+
+    assert_eq!(results, vec![expr!(wrd(A))]);
+}
+
+#[test]
+fn defer_disequality_on_unbound_variables() {
+    // ctx[x : A, y : B] has ? : ?
+
+    let query = expr!(seq(
+        lst(ctx, [seq(wrd(x), wrd(:), wrd(A)), seq(wrd(y), wrd(:), wrd(B)),]),
+        wrd(has),
+        var(name),
+        wrd(:),
+        var(type)
+    ));
+
+    let results: Vec<_> = context_rules()
+        .derive(&query)
+        .map(|(s, _)| s.resolve(&expr!(seq(var(name), var(type)))))
+        .collect();
+
+    assert_eq!(
+        results,
+        vec![expr!(seq(wrd(x), wrd(A))), expr!(seq(wrd(y), wrd(B)))]
+    );
+}
+
+fn freshness_rules() -> RuleSet {
+    let mut rules = RuleSet::new();
+
+    //  $x in ctx[$x | $rest]
+
+    rules.insert(
+        "in-here",
+        &expr!(seq(var(x), wrd(in), lst(ctx, [var(x) | var(rest)]))),
+        &[],
+    );
+
+    //      $x in $rest
+    //  ---------------------
+    //  $x in ctx[$y | $rest]
+
+    rules.insert(
+        "in-there",
+        &expr!(seq(var(x), wrd(in), lst(ctx, [var(y) | var(rest)]))),
+        &[expr!(seq(var(x), wrd(in), var(rest)))],
+    );
+
+    //  not ($x in $ctx)
+    //  ----------------
+    //   $x fresh $ctx
+
+    rules.insert(
+        "fresh",
+        &expr!(seq(var(x), wrd(fresh), var(ctx))),
+        &[expr!(seq(wrd(not), seq(var(x), wrd(in), var(ctx))))],
+    );
+
+    rules
+}
+
+#[test]
+fn derive_negated_premise_without_solutions() {
+    let query = expr!(seq(wrd(c), wrd(fresh), lst(ctx, [wrd(a), wrd(b),])));
+    let proofs: Vec<_> = freshness_rules().derive(&query).collect();
+
+    assert_eq!(proofs.len(), 1);
+    assert_eq!(
+        format!("{:?}", proofs[0].1),
+        "    [fresh] c fresh ctx[a, b]\n        [not] not (c in ctx[a, b])\n"
+    );
+}
+
+#[test]
+fn reject_negated_premise_with_solutions() {
+    let query = expr!(seq(wrd(b), wrd(fresh), lst(ctx, [wrd(a), wrd(b),])));
+    assert_eq!(freshness_rules().derive(&query).count(), 0);
+}
+
+#[test]
+fn negate_premises_written_without_parentheses() {
+    let rules = load(
+        "
+        rule in-here
+            $x in ctx[$x | $rest]
+        rule in-there
+            $x in ctx[$y | $rest] :- $x in $rest
+        rule fresh
+            $x fresh $c :- not $x in $c
+        ",
+    )
+    .unwrap();
+
+    assert_eq!(answers(&rules, "c fresh ctx[a, b]").len(), 1);
+    assert_eq!(answers(&rules, "b fresh ctx[a, b]").len(), 0);
+
+    let (_, proof) = rules
+        .derive(&parse("c fresh ctx[a, b]").unwrap())
+        .next()
+        .unwrap();
+    assert_eq!(rules.check(&proof), Ok(()));
+}
+
+#[test]
+fn bound_search_for_negated_premises() {
+    let mut rules = RuleSet::new();
+
+    rules.insert("loop", &expr!(wrd(loop)), &[expr!(wrd(loop))]);
+    rules.insert(
+        "halt",
+        &expr!(wrd(halt)),
+        &[expr!(seq(wrd(not), wrd(loop)))],
+    );
+
+    assert_eq!(rules.derive(&expr!(wrd(halt))).count(), 0);
+}
+
+#[test]
+fn configure_depth_of_negated_premises() {
+    let mut rules = RuleSet::new();
+
+    for i in 1..=20 {
+        let rule = format!("rule n-{}\n    n{} ok :- n{} ok\n", i, i, i - 1);
+        rules.load_str(&rule).unwrap();
+    }
+    rules
+        .load_str("rule check\n    check :- not (n20 ok)")
+        .unwrap();
+
+    assert_eq!(rules.derive(&expr!(wrd(check))).count(), 0);
+
+    rules.set_negation_depth(32);
+    assert_eq!(rules.derive(&expr!(wrd(check))).count(), 1);
+}
+
+#[test]
+fn reject_negated_premise_derivable_beyond_depth() {
+    let mut rules = RuleSet::new();
+
+    rules.load_str("rule n-0\n    n0 ok").unwrap();
+    for i in 1..=20 {
+        let rule = format!("rule n-{}\n    n{} ok :- n{} ok\n", i, i, i - 1);
+        rules.load_str(&rule).unwrap();
+    }
+    rules
+        .load_str("rule check\n    check :- not (n20 ok)")
+        .unwrap();
+
+    assert_eq!(rules.derive(&expr!(wrd(check))).count(), 0);
+
+    let limits = Limits {
+        max_depth: Some(8),
+        ..Limits::default()
+    };
+    let mut derivation = rules.derive_with_limits(&expr!(wrd(check)), limits);
+
+    assert!(derivation.next().is_none());
+    assert_eq!(derivation.status(), Status::DepthLimit);
+}
+
+#[test]
+fn add_numbers_in_any_direction() {
+    let rules = RuleSet::new();
+
+    assert_eq!(
+        answers(&rules, "add 2 3 $c"),
+        vec![parse("add 2 3 5").unwrap()]
+    );
+    assert_eq!(
+        answers(&rules, "add 2 $b 5"),
+        vec![parse("add 2 3 5").unwrap()]
+    );
+    assert_eq!(
+        answers(&rules, "add $a 3 5"),
+        vec![parse("add 2 3 5").unwrap()]
+    );
+    assert_eq!(
+        answers(&rules, "sub 2 5 $c"),
+        vec![parse("sub 2 5 -3").unwrap()]
+    );
+    assert_eq!(answers(&rules, "add 2 3 6").len(), 0);
+}
+
+#[test]
+fn multiply_and_divide_numbers() {
+    let rules = RuleSet::new();
+
+    assert_eq!(
+        answers(&rules, "mul 4 $b 12"),
+        vec![parse("mul 4 3 12").unwrap()]
+    );
+    assert_eq!(answers(&rules, "mul 4 $b 13").len(), 0);
+    assert_eq!(
+        answers(&rules, "mul 0 $b 0"),
+        vec![parse("mul 0 $b 0").unwrap()]
+    );
+    assert_eq!(answers(&rules, "mul 0 $b 1").len(), 0);
+}
+
+#[test]
+fn compare_numbers() {
+    let rules = RuleSet::new();
+
+    assert_eq!(answers(&rules, "1 < 2").len(), 1);
+    assert_eq!(answers(&rules, "2 < 2").len(), 0);
+    assert_eq!(answers(&rules, "2 <= 2").len(), 1);
+    assert_eq!(answers(&rules, "3 > 2").len(), 1);
+    assert_eq!(answers(&rules, "2 >= 3").len(), 0);
+}
+
+#[test]
+fn fail_arithmetic_on_unknown_values() {
+    let rules = RuleSet::new();
+
+    assert_eq!(answers(&rules, "add $a $b 5").len(), 0);
+    assert_eq!(answers(&rules, "add a 1 $c").len(), 0);
+    assert_eq!(answers(&rules, "$x < 2").len(), 0);
+}
+
+#[test]
+fn defer_to_rules_for_non_numeric_arithmetic() {
+    let rules = load(
+        "
+        rule add-Z
+            add z $n $n
+
+        rule add-S
+            add (s $m) $n (s $k) :- add $m $n $k
+
+        rule a-lt-b
+            a < b
+        ",
+    )
+    .unwrap();
+
+    assert_eq!(
+        answers(&rules, "add (s z) z $r"),
+        vec![parse("add (s z) z (s z)").unwrap()]
+    );
+    assert_eq!(answers(&rules, "a < b").len(), 1);
+    assert_eq!(answers(&rules, "b < a").len(), 0);
+
+    assert_eq!(
+        answers(&rules, "add 2 3 $c"),
+        vec![parse("add 2 3 5").unwrap()]
+    );
+    assert_eq!(answers(&rules, "1 < 2").len(), 1);
+}
+
+#[test]
+fn evaluate_recursive_arithmetic() {
+    let rules = load(
+        "
+        rule fact-0
+            fact 0 = 1
+
+        rule fact-N
+            $n > 0, sub $n 1 $m, fact $m = $r, mul $n $r $f
+            ---------------------------------------------
+            fact $n = $f
+        ",
+    )
+    .unwrap();
+
+    assert_eq!(
+        answers(&rules, "fact 5 = $f"),
+        vec![parse("fact 5 = 120").unwrap()]
+    );
+
+    let (_, proof) = rules.derive(&parse("fact 1 = $f").unwrap()).next().unwrap();
+    assert_eq!(
+        format!("{:?}", proof),
+        "    [fact-N] fact 1 = 1\n        [>] 1 > 0\n        [sub] sub 1 1 0\n        \
+         [fact-0] fact 0 = 1\n        [mul] mul 1 1 1\n"
+    );
+}
+
+#[test]
+fn concatenate_strings_in_any_direction() {
+    let rules = RuleSet::new();
+
+    assert_eq!(
+        answers(&rules, r#"concat "ab" "cd" $c"#),
+        vec![parse(r#"concat "ab" "cd" "abcd""#).unwrap()]
+    );
+    assert_eq!(
+        answers(&rules, r#"concat "ab" $b "abcd""#),
+        vec![parse(r#"concat "ab" "cd" "abcd""#).unwrap()]
+    );
+    assert_eq!(
+        answers(&rules, r#"concat $a "cd" "abcd""#),
+        vec![parse(r#"concat "ab" "cd" "abcd""#).unwrap()]
+    );
+    assert_eq!(answers(&rules, r#"concat $a "x" "abcd""#).len(), 0);
+}
+
+#[test]
+fn enumerate_splits_of_a_string() {
+    assert_eq!(
+        answers(&RuleSet::new(), r#"concat $a $b "aλ""#),
+        vec![
+            parse(r#"concat "" "aλ" "aλ""#).unwrap(),
+            parse(r#"concat "a" "λ" "aλ""#).unwrap(),
+            parse(r#"concat "aλ" "" "aλ""#).unwrap(),
+        ]
+    );
+}
+
+#[test]
+fn measure_string_length() {
+    let rules = RuleSet::new();
+
+    assert_eq!(
+        answers(&rules, r#"length "aλ c" $n"#),
+        vec![parse(r#"length "aλ c" 4"#).unwrap()]
+    );
+    assert_eq!(answers(&rules, "length $s 3").len(), 0);
+}
+
+#[test]
+fn defer_to_rules_for_non_string_relations() {
+    let rules = load(
+        "
+        rule length-0
+            length k[] z
+
+        rule length-N
+            length k[$h | $t] (s $n) :- length $t $n
+
+        rule concat-0
+            concat k[] $l $l
+
+        rule concat-N
+            concat k[$h | $t] $l k[$h | $r] :- concat $t $l $r
+        ",
+    )
+    .unwrap();
+
+    assert_eq!(
+        answers(&rules, "length k[a, b] $n"),
+        vec![parse("length k[a, b] (s (s z))").unwrap()]
+    );
+    assert_eq!(
+        answers(&rules, "concat k[a] k[b] $l"),
+        vec![parse("concat k[a] k[b] k[a, b]").unwrap()]
+    );
+    assert_eq!(
+        answers(&rules, r#"length "ab" $n"#),
+        vec![parse(r#"length "ab" 2"#).unwrap()]
+    );
+}
+
+#[test]
+fn load_rules_with_escaped_words() {
+    let rules = load(
+        r#"
+        rule spaced
+            say\ hi \#1 \"quoted\"
+        "#,
+    )
+    .unwrap();
+
+    let word = |name: &str| Expr::Wrd(Rc::new(Word(String::from(name))));
+    let goal = Expr::Seq(Rc::new(Sequence(vec![
+        word("say hi"),
+        word("#1"),
+        word("\"quoted\""),
+    ])));
+
+    assert_eq!(rules.derive(&goal).count(), 1);
+}
+
+#[test]
+fn load_rules_with_strings() {
+    let rules = load(
+        r#"
+        rule greet   # comment
+            concat "hello, # " $name $text
+            ------------------------------
+            greet $name = $text
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        answers(&rules, r#"greet "a :- b" = $t"#),
+        vec![parse(r#"greet "a :- b" = "hello, # a :- b""#).unwrap()]
+    );
+    assert_eq!(
+        load(&rules.to_string()).unwrap().to_string(),
+        rules.to_string()
+    );
+}
+
+fn strategy_answers(rules: &RuleSet, query: &str, strategy: SearchStrategy) -> Vec<Expr> {
+    let query = parse(query).unwrap();
+
+    rules
+        .derive_with(&query, strategy)
+        .map(|(s, _)| s.resolve(&query))
+        .collect()
+}
+
+#[test]
+fn search_depth_first_in_rule_order() {
+    let rules = load(
+        "
+        rule p-a
+            p a
+        rule p-q
+            p $x :- q $x
+        rule p-d
+            p d
+        rule q-b
+            q b
+        rule q-c
+            q c
+        ",
+    )
+    .unwrap();
+
+    let depth_first = strategy_answers(&rules, "p $x", SearchStrategy::DepthFirst);
+    assert_eq!(
+        depth_first,
+        ["p a", "p b", "p c", "p d"]
+            .iter()
+            .map(|q| parse(q).unwrap())
+            .collect::<Vec<_>>()
+    );
+
+    let mut fair = strategy_answers(&rules, "p $x", SearchStrategy::Interleave);
+    assert_ne!(fair, depth_first);
+
+    fair.sort_by_key(Expr::to_string);
+    assert_eq!(fair, depth_first);
+}
+
+#[test]
+fn search_iteratively_deepening_to_exhaustion() {
+    let query = expr!(seq(
+        var(x),
+        wrd(plus),
+        var(y),
+        wrd(eq),
+        seq(wrd(a), seq(wrd(b), wrd(nil)))
+    ));
+    let rules = append_rules();
+
+    let mut expected: Vec<_> = rules
+        .derive(&query)
+        .map(|(s, _)| s.resolve(&query))
+        .collect();
+    let mut found: Vec<_> = rules
+        .derive_with(&query, SearchStrategy::IterativeDeepening)
+        .map(|(s, _)| s.resolve(&query))
+        .collect();
+
+    expected.sort_by_key(Expr::to_string);
+    found.sort_by_key(Expr::to_string);
+    assert_eq!(found, expected);
+}
+
+#[test]
+fn search_iteratively_deepening_in_order_of_proof_height() {
+    let query = expr!(seq(wrd(a), wrd(sub), var(y)));
+    let rules = transitive_rules();
+
+    let proofs: Vec<_> = rules
+        .derive_with(&query, SearchStrategy::IterativeDeepening)
+        .take(50)
+        .collect();
+
+    let heights: Vec<_> = proofs.iter().map(|(_, proof)| proof.depth()).collect();
+    assert!(heights.windows(2).all(|pair| pair[0] <= pair[1]));
+
+    let answers: Vec<_> = proofs
+        .iter()
+        .map(|(s, _)| s.resolve(&expr!(var(y))))
+        .collect();
+    assert_eq!(answers[0..2], [expr!(wrd(a)), expr!(wrd(b))]);
+    assert!(answers.contains(&expr!(wrd(d))));
+}
+
+#[test]
+fn search_iteratively_deepening_without_solutions() {
+    let query = parse("(a (b nil)) plus nil eq (a nil)").unwrap();
+    let rules = append_rules();
+
+    let mut proofs = rules.derive_with(&query, SearchStrategy::IterativeDeepening);
+    assert!(proofs.next().is_none());
+}
+
+fn limits(
+    max_depth: Option<usize>,
+    max_steps: Option<usize>,
+    max_solutions: Option<usize>,
+) -> Limits {
+    Limits {
+        max_depth,
+        max_steps,
+        max_solutions,
+    }
+}
+
+fn proof_keys(proofs: &[(State, Rc<Proof>)]) -> Vec<String> {
+    proofs
+        .iter()
+        .map(|(_, proof)| format!("{:?}", proof))
+        .collect()
+}
+
+#[test]
+fn stop_derivation_at_solution_limit() {
+    let rules = transitive_rules();
+    let query = expr!(seq(var(x), wrd(sub), var(z)));
+
+    let mut derivation = rules.derive_with_limits(&query, limits(None, None, Some(5)));
+    let first: Vec<_> = derivation.by_ref().collect();
+
+    assert_eq!(first.len(), 5);
+    assert_eq!(derivation.status(), Status::SolutionLimit);
+
+    derivation.resume(limits(None, None, Some(8)));
+    let second: Vec<_> = derivation.by_ref().collect();
+
+    assert_eq!(second.len(), 3);
+    assert_eq!(derivation.status(), Status::SolutionLimit);
+
+    let expected: Vec<_> = rules.derive(&query).take(8).collect();
+    assert_eq!(proof_keys(&[first, second].concat()), proof_keys(&expected));
+}
+
+#[test]
+fn stop_fruitless_derivation_at_step_limit() {
+    let rules = transitive_rules();
+    let query = expr!(seq(wrd(d), wrd(sub), wrd(a)));
+
+    let mut derivation = rules.derive_with_limits(&query, limits(None, Some(1000), None));
+
+    assert!(derivation.next().is_none());
+    assert_eq!(derivation.status(), Status::StepLimit);
+
+    derivation.resume(limits(None, Some(2000), None));
+
+    assert!(derivation.next().is_none());
+    assert_eq!(derivation.status(), Status::StepLimit);
+}
+
+#[test]
+fn stop_fruitless_derivation_at_depth_limit() {
+    let rules = transitive_rules();
+    let query = expr!(seq(wrd(d), wrd(sub), wrd(a)));
+
+    let mut derivation = rules.derive_with_limits(&query, limits(Some(4), None, None));
+
+    assert!(derivation.next().is_none());
+    assert_eq!(derivation.status(), Status::DepthLimit);
+}
+
+#[test]
+fn exhaust_derivation_within_limits() {
+    let rules = append_rules();
+    let query = parse("$x plus $y eq (a (b nil))").unwrap();
+
+    let mut derivation = rules.derive_with_limits(&query, limits(Some(10), Some(1000), Some(10)));
+
+    assert_eq!(derivation.by_ref().count(), 3);
+    assert_eq!(derivation.status(), Status::Exhausted);
+}
+
+#[test]
+fn resume_derivation_without_repeating_solutions() {
+    let rules = transitive_rules();
+    let query = expr!(seq(wrd(a), wrd(sub), var(y)));
+
+    let mut derivation = rules.derive_with_limits(&query, limits(Some(2), None, None));
+    let first: Vec<_> = derivation.by_ref().collect();
+
+    assert_eq!(derivation.status(), Status::DepthLimit);
+
+    derivation.resume(limits(Some(3), None, None));
+    let second: Vec<_> = derivation.by_ref().collect();
+
+    assert_eq!(derivation.status(), Status::DepthLimit);
+
+    let all: Vec<_> = rules
+        .derive_with_limits(&query, limits(Some(3), None, None))
+        .collect();
+
+    let mut keys = proof_keys(&[first.clone(), second].concat());
+    keys.sort();
+    let mut expected = proof_keys(&all);
+    expected.sort();
+
+    assert!(!first.is_empty());
+    assert_eq!(keys, expected);
+}
+
+#[test]
+fn table_left_recursive_rules() {
+    let mut rules = transitive_rules();
+    rules.table(&expr!(seq(var(x), wrd(sub), var(y))));
+
+    let query = expr!(seq(wrd(a), wrd(sub), var(y)));
+    let answers: Vec<_> = rules
+        .derive(&query)
+        .map(|(s, _)| s.resolve(&expr!(var(y))))
+        .collect();
+
+    assert_eq!(
+        answers,
+        vec![expr!(wrd(a)), expr!(wrd(b)), expr!(wrd(c)), expr!(wrd(d))]
+    );
+}
+
+#[test]
+fn table_goals_without_answers() {
+    let mut rules = transitive_rules();
+    rules.table(&expr!(seq(var(x), wrd(sub), var(y))));
+
+    let query = expr!(seq(wrd(d), wrd(sub), wrd(a)));
+    assert_eq!(rules.derive(&query).count(), 0);
+}
+
+#[test]
+fn table_cyclic_paths() {
+    let mut rules = load(
+        "
+        rule edge-ab
+            edge a b
+        rule edge-bc
+            edge b c
+        rule edge-ca
+            edge c a
+        rule path-1
+            path $x $y :- edge $x $y
+        rule path-N
+            path $x $z :- path $x $y, edge $y $z
+        ",
+    )
+    .unwrap();
+
+    rules.table(&parse("path $x $y").unwrap());
+
+    let mut found = answers(&rules, "path $x $y");
+    found.sort_by_key(Expr::to_string);
+
+    let mut expected = Vec::new();
+    for x in &["a", "b", "c"] {
+        for y in &["a", "b", "c"] {
+            expected.push(parse(&format!("path {} {}", x, y)).unwrap());
+        }
+    }
+    assert_eq!(found, expected);
+
+    assert_eq!(answers(&rules, "path c $y").len(), 3);
+}
+
+#[test]
+fn show_proofs_of_tabled_answers() {
+    let mut rules = transitive_rules();
+    rules.table(&expr!(seq(var(x), wrd(sub), var(y))));
+
+    let query = expr!(seq(wrd(a), wrd(sub), wrd(c)));
+    let proofs: Vec<_> = rules.derive(&query).collect();
+
+    assert_eq!(proofs.len(), 1);
+    assert!(
+        format!("{:?}", proofs[0].1).starts_with("    [S-Trans] a sub c\n        [S-AB] a sub b\n")
+    );
+}
+
+#[test]
+fn table_goals_within_depth_limits() {
+    let mut rules = load(
+        "
+        rule p
+            $x p $y :- $x q $y
+        rule q
+            $x q $y :- $x q $y
+        ",
+    )
+    .unwrap();
+
+    rules.table(&parse("$x p $y").unwrap());
+
+    let limits = Limits {
+        max_depth: Some(5),
+        ..Limits::default()
+    };
+    let mut derivation = rules.derive_with_limits(&parse("a p $w").unwrap(), limits);
+
+    assert!(derivation.next().is_none());
+    assert_eq!(derivation.status(), Status::DepthLimit);
+}
+
+#[test]
+fn table_goals_with_iterative_deepening() {
+    let mut rules = load(
+        "
+        rule edge-ab
+            edge a b
+        rule edge-bc
+            edge b c
+        rule edge-ca
+            edge c a
+        rule path-1
+            path $x $y :- edge $x $y
+        rule path-N
+            path $x $z :- path $x $y, edge $y $z
+        ",
+    )
+    .unwrap();
+
+    rules.table(&parse("path $x $y").unwrap());
+
+    let query = parse("path a $y").unwrap();
+    let strategy = SearchStrategy::IterativeDeepening;
+    let found: Vec<_> = rules
+        .derive_with(&query, strategy)
+        .map(|(s, _)| s.resolve(&query))
+        .collect();
+
+    assert_eq!(
+        found,
+        vec![
+            parse("path a b").unwrap(),
+            parse("path a c").unwrap(),
+            parse("path a a").unwrap(),
+        ]
+    );
+
+    let limits = Limits {
+        max_depth: Some(3),
+        ..Limits::default()
+    };
+    let mut derivation = rules.derive_with_limits(&query, limits);
+
+    assert_eq!(derivation.by_ref().count(), 2);
+    assert_eq!(derivation.status(), Status::DepthLimit);
+}
+
+#[test]
+fn complete_tables_before_negating_them() {
+    let mut rules = load(
+        "
+        rule edge-ab
+            edge a b
+        rule edge-bc
+            edge b c
+        rule edge-cd
+            edge c d
+        rule path-1
+            path $x $y :- edge $x $y
+        rule path-N
+            path $x $z :- path $x $y, edge $y $z
+        rule unreachable
+            unreachable $x $y :- not (path $x $y)
+        ",
+    )
+    .unwrap();
+
+    rules.table(&parse("path $x $y").unwrap());
+    rules.table(&parse("unreachable $x $y").unwrap());
+
+    for strategy in &[SearchStrategy::Interleave, SearchStrategy::DepthFirst] {
+        let derive = |query: &str| rules.derive_with(&parse(query).unwrap(), *strategy).count();
+
+        assert_eq!(derive("unreachable a d"), 0);
+        assert_eq!(derive("unreachable d a"), 1);
+    }
+
+    rules.load_str("rule liar\n    liar :- not liar").unwrap();
+    rules.table(&parse("liar").unwrap());
+
+    assert_eq!(rules.derive(&parse("liar").unwrap()).count(), 0);
+}
+
+#[test]
+fn keep_constraints_on_tabled_answers() {
+    let mut rules = load(
+        "
+        rule ok
+            $x ok :- $x != a
+        rule eq
+            eq $x $x
+        rule test
+            test :- $y ok, eq $y a
+        rule other
+            other :- $y ok, eq $y b
+        ",
+    )
+    .unwrap();
+
+    assert_eq!(rules.derive(&parse("test").unwrap()).count(), 0);
+
+    rules.table(&parse("$x ok").unwrap());
+
+    assert_eq!(rules.derive(&parse("test").unwrap()).count(), 0);
+    assert_eq!(rules.derive(&parse("other").unwrap()).count(), 1);
+}
+
+#[test]
+fn select_rules_by_head_shape() {
+    let mut index = Index::default();
+
+    for (i, head) in [
+        "a", "$x", "f $x", "g $x", "$f $x", "f $x $y", "k[]", "j[$x]", "1", "\"s\"",
+    ]
+    .iter()
+    .enumerate()
+    {
+        index.insert(i, &parse(head).unwrap());
+    }
+
+    let candidates = |target: &str| {
+        let mut state = State::new();
+        state = state
+            .unify((0, &expr!(var(w))), (0, &expr!(wrd(g))))
+            .unwrap();
+        index.candidates(&state, (0, &parse(target).unwrap()))
+    };
+
+    assert_eq!(candidates("a"), Some(vec![0, 1]));
+    assert_eq!(candidates("b"), Some(vec![1]));
+    assert_eq!(candidates("$v"), None);
+    assert_eq!(candidates("f b"), Some(vec![1, 2, 4]));
+    assert_eq!(candidates("$w b"), Some(vec![1, 3, 4]));
+    assert_eq!(candidates("$v b"), Some(vec![1, 2, 3, 4]));
+    assert_eq!(candidates("(f) b"), Some(vec![1, 4]));
+    assert_eq!(candidates("f b c"), Some(vec![1, 5]));
+    assert_eq!(candidates("k[a]"), Some(vec![1, 6]));
+    assert_eq!(candidates("1"), Some(vec![1, 8]));
+    assert_eq!(candidates("\"s\""), Some(vec![1, 9]));
+}
+
+#[test]
+fn derive_same_answers_with_and_without_index() {
+    let source = "
+        rule var-head
+            $x
+        rule pair
+            pair $a $b :- $a, $b
+        rule word
+            ok
+        rule list
+            ok[$x] :- $x
+        rule lead-var
+            $f ok
+        ";
+
+    let mut unindexed = load(source).unwrap();
+    unindexed.set_indexing(false);
+    let indexed = load(source).unwrap();
+
+    for query in &["ok", "pair ok ok", "$f ok", "ok[ok]", "pair $x ok"] {
+        let query = parse(query).unwrap();
+
+        let answers = |rules: &RuleSet| -> Vec<_> {
+            let proofs = rules.derive_with_limits(&query, limits(Some(3), None, None));
+            let mut keys: Vec<_> = proofs.map(|(_, proof)| format!("{:?}", proof)).collect();
+            keys.sort();
+            keys
+        };
+
+        assert_eq!(answers(&indexed), answers(&unindexed));
+    }
+}
+
+#[test]
+fn reindex_replaced_rules() {
+    let mut rules = RuleSet::new();
+
+    rules.insert("r", &expr!(wrd(a)), &[]);
+    rules.insert("r", &expr!(wrd(b)), &[]);
+
+    assert_eq!(rules.derive(&expr!(wrd(a))).count(), 0);
+    assert_eq!(rules.derive(&expr!(wrd(b))).count(), 1);
+}
+
+#[test]
+fn select_rules_by_most_specific_column() {
+    let mut index = Index::default();
+
+    for (i, head) in [
+        "eval (op1 $x)",
+        "eval (op2 $x)",
+        "eval ($f $x)",
+        "eval (a b c)",
+        "eval a",
+    ]
+    .iter()
+    .enumerate()
+    {
+        index.insert(i, &parse(head).unwrap());
+    }
+
+    let candidates = |target: &str| index.candidates(&State::new(), (0, &parse(target).unwrap()));
+
+    assert_eq!(candidates("eval (op1 a)"), Some(vec![0, 2]));
+    assert_eq!(candidates("eval ($g a)"), Some(vec![0, 1, 2]));
+    assert_eq!(candidates("eval ((op1) a)"), Some(vec![2]));
+    assert_eq!(candidates("eval $e"), Some(vec![0, 1, 2, 3, 4]));
+    assert_eq!(candidates("eval a"), Some(vec![4]));
+}
+
+fn tree(rule: &str, conclusion: &str, premises: Vec<ProofTree>) -> ProofTree {
+    ProofTree {
+        rule: String::from(rule),
+        conclusion: parse(conclusion).unwrap(),
+        premises,
+    }
+}
+
+fn checked_rules() -> RuleSet {
+    let mut rules = RuleSet::new();
+
+    rules
+        .load_str(
+            "
+            rule S-AB
+                a <: b
+            rule S-BC
+                b <: c
+            rule S-Trans
+                $x <: $z :- $x <: $y, $y <: $z
+            rule S-Top
+                $x <: top
+            rule S-Not
+                excluded $x :- $x != b, not (minimal $x)
+            ",
+        )
+        .unwrap();
+
+    rules.set_negation_depth(4);
+    rules
+}
+
+#[test]
+fn check_derived_proofs() {
+    let rules = checked_rules();
+
+    for query in &["a <: c", "excluded c"] {
+        let (_, proof) = rules.derive(&parse(query).unwrap()).next().unwrap();
+        assert_eq!(rules.check(&proof), Ok(()));
+    }
+
+    let (_, proof) = append_rules().derive(&query_append()).next().unwrap();
+    assert_eq!(append_rules().check(&proof), Ok(()));
+}
+
+#[test]
+fn check_proofs_with_variables_from_different_rules() {
+    let rules = load(
+        "
+        rule any
+            any $z
+        rule two
+            two $a $b :- any $a, any $b, $a != $b
+        ",
+    )
+    .unwrap();
+
+    let (_, proof) = rules.derive(&parse("two $p $q").unwrap()).next().unwrap();
+    let tree = proof.to_tree();
+
+    assert_eq!(tree.conclusion, parse("two $_0 $_1").unwrap());
+    assert_eq!(tree.premises[2].conclusion, parse("$_0 != $_1").unwrap());
+    assert_eq!(rules.check(&proof), Ok(()));
+}
+
+fn query_append() -> Expr {
+    parse("(a (b nil)) plus (c nil) eq $x").unwrap()
+}
+
+#[test]
+fn check_external_proof_trees() {
+    let rules = checked_rules();
+
+    let proof = tree(
+        "S-Trans",
+        "a <: c",
+        vec![
+            tree("S-AB", "a <: b", vec![]),
+            tree("S-BC", "b <: c", vec![]),
+        ],
+    );
+    assert_eq!(rules.check_tree(&proof), Ok(()));
+
+    let proof = tree("S-Top", "$t <: top", vec![]);
+    assert_eq!(rules.check_tree(&proof), Ok(()));
+}
+
+#[test]
+fn reject_unknown_rules() {
+    let proof = tree("S-AC", "a <: c", vec![]);
+
+    assert_eq!(
+        checked_rules().check_tree(&proof),
+        Err(CheckError::UnknownRule(String::from("S-AC")))
+    );
+}
+
+#[test]
+fn reject_wrong_number_of_premises() {
+    let proof = tree("S-Trans", "a <: c", vec![tree("S-AB", "a <: b", vec![])]);
+
+    assert_eq!(
+        checked_rules().check_tree(&proof),
+        Err(CheckError::PremiseCount {
+            rule: String::from("S-Trans"),
+            expected: 2,
+            found: 1,
+        })
+    );
+}
+
+#[test]
+fn reject_inconsistent_substitutions() {
+    let rules = checked_rules();
+
+    let proof = tree(
+        "S-Trans",
+        "a <: c",
+        vec![
+            tree("S-AB", "a <: b", vec![]),
+            tree("S-Top", "top <: c", vec![]),
+        ],
+    );
+    assert_eq!(
+        rules.check_tree(&proof),
+        Err(CheckError::Mismatch {
+            rule: String::from("S-Trans"),
+            conclusion: parse("a <: c").unwrap(),
+        })
+    );
+
+    let proof = tree("S-AB", "a <: c", vec![]);
+    assert!(rules.check_tree(&proof).is_err());
+
+    let proof = tree("S-AB", "$x <: b", vec![]);
+    assert!(rules.check_tree(&proof).is_err());
+}
+
+#[test]
+fn reject_invalid_subproofs() {
+    let proof = tree(
+        "S-Trans",
+        "a <: c",
+        vec![
+            tree("S-AB", "a <: b", vec![]),
+            tree("S-AB", "b <: c", vec![]),
+        ],
+    );
+
+    assert_eq!(
+        checked_rules().check_tree(&proof),
+        Err(CheckError::Mismatch {
+            rule: String::from("S-AB"),
+            conclusion: parse("b <: c").unwrap(),
+        })
+    );
+}
+
+#[test]
+fn reject_false_builtins_and_negations() {
+    let rules = checked_rules();
+
+    let proof = tree(
+        "S-Not",
+        "excluded b",
+        vec![
+            tree("!=", "b != b", vec![]),
+            tree("not", "not (minimal b)", vec![]),
+        ],
+    );
+    assert_eq!(
+        rules.check_tree(&proof),
+        Err(CheckError::Mismatch {
+            rule: String::from("!="),
+            conclusion: parse("b != b").unwrap(),
+        })
+    );
+
+    let proof = tree(
+        "S-Not",
+        "excluded a",
+        vec![
+            tree("!=", "a != b", vec![]),
+            tree("not", "not (minimal a)", vec![]),
+        ],
+    );
+    assert_eq!(rules.check_tree(&proof), Ok(()));
+
+    let mut rules = rules;
+    rules.load_str("rule M-A\n    minimal a").unwrap();
+
+    assert_eq!(
+        rules.check_tree(&proof),
+        Err(CheckError::Derivable(parse("minimal a").unwrap()))
+    );
+}
+
+#[test]
+fn reject_negations_cut_off_by_the_search_depth() {
+    let mut rules = RuleSet::new();
+
+    rules.load_str("rule n-0\n    n0 ok").unwrap();
+    for i in 1..=3 {
+        let rule = format!("rule n-{}\n    n{} ok :- n{} ok\n", i, i, i - 1);
+        rules.load_str(&rule).unwrap();
+    }
+    rules.set_negation_depth(2);
+
+    let proof = tree("not", "not (n3 ok)", vec![]);
+    assert_eq!(
+        rules.check_tree(&proof),
+        Err(CheckError::Undecided(parse("n3 ok").unwrap()))
+    );
+
+    rules.set_negation_depth(8);
+    assert_eq!(
+        rules.check_tree(&proof),
+        Err(CheckError::Derivable(parse("n3 ok").unwrap()))
+    );
+}
+
+#[test]
+fn query_bindings_of_each_variable() {
+    let mut rules = RuleSet::new();
+    rules
+        .load_file(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/examples/append.rules"
+        ))
+        .unwrap();
+
+    let query = parse("$x + $y = λ[a, b]").unwrap();
+    let solutions: Vec<_> = rules.query(&query).map(|s| s.to_string()).collect();
+
+    assert_eq!(
+        solutions,
+        vec![
+            "$x = λ[], $y = λ[a, b]",
+            "$x = λ[a], $y = λ[b]",
+            "$x = λ[a, b], $y = λ[]",
+        ]
+    );
+}
+
+#[test]
+fn look_up_solution_bindings_by_name() {
+    let mut rules = RuleSet::new();
+    rules
+        .load_file(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/examples/append.rules"
+        ))
+        .unwrap();
+
+    let query = parse("λ[a | $t] + $y = $z").unwrap();
+    let solution = rules.query(&query).nth(1).unwrap();
+
+    let names: Vec<_> = solution.iter().map(|(name, _)| name).collect();
+    assert_eq!(names, vec!["t", "y", "z"]);
+
+    assert_eq!(solution.get("t"), Some(&parse("λ[$_0]").unwrap()));
+    assert_eq!(solution.get("z"), Some(&parse("λ[a, $_0 | $_1]").unwrap()));
+    assert_eq!(solution.get("w"), None);
+    assert_eq!(solution.proof().rule(), "append-N");
+}
+
+#[test]
+fn show_ground_solutions_as_true() {
+    let rules = append_rules();
+    let query = parse("nil plus nil eq nil").unwrap();
+
+    let solutions: Vec<_> = rules.query(&query).collect();
+
+    assert_eq!(solutions.len(), 1);
+    assert!(solutions[0].is_empty());
+    assert_eq!(solutions[0].to_string(), "true");
+}
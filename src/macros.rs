@@ -16,6 +16,12 @@ macro_rules! expr {
     (wrd($x:tt)) => {
         expr!(@wrap Wrd Word(String::from(stringify!($x))))
     };
+    (num($n:expr)) => {
+        expr!(@wrap Num Number(BigInt::from($n)))
+    };
+    (str($s:expr)) => {
+        expr!(@wrap Str Text(String::from($s)))
+    };
     (seq($( $n:ident $a:tt ),+)) => {
         expr!(@wrap Seq Sequence(vec![$( expr!($n $a) ),+]))
     };
@@ -0,0 +1,90 @@
+use super::Proof;
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DotOptions {
+    pub merge_identical: bool,
+}
+
+impl Proof {
+    pub fn to_dot(&self) -> String {
+        self.to_dot_with(&DotOptions::default())
+    }
+
+    pub fn to_dot_with(&self, options: &DotOptions) -> String {
+        let mut dot = Dot {
+            options,
+            nodes: HashMap::new(),
+            count: 0,
+            output: String::new(),
+        };
+
+        dot.output.push_str("digraph proof {\n");
+        dot.output.push_str("  rankdir=BT;\n");
+        dot.output.push_str("  node [shape=box];\n");
+        dot.node(self);
+        dot.output.push_str("}\n");
+
+        dot.output
+    }
+}
+
+struct Dot<'a> {
+    options: &'a DotOptions,
+    nodes: HashMap<String, usize>,
+    count: usize,
+    output: String,
+}
+
+impl Dot<'_> {
+    fn node(&mut self, proof: &Proof) -> usize {
+        let premises: Vec<_> = proof
+            .parents
+            .iter()
+            .map(|parent| self.node(parent))
+            .collect();
+        let label = format!(
+            "{}\\n{}",
+            escape(&proof.rule),
+            escape(&proof.conclusion().to_string())
+        );
+        let key = format!("{}{:?}", label, premises);
+
+        if self.options.merge_identical {
+            if let Some(id) = self.nodes.get(&key) {
+                return *id;
+            }
+        }
+
+        let id = self.count;
+        self.count += 1;
+        self.nodes.insert(key, id);
+
+        self.output
+            .push_str(&format!("  n{} [label=\"{}\"];\n", id, label));
+
+        for premise in premises {
+            self.output
+                .push_str(&format!("  n{} -> n{};\n", id, premise));
+        }
+
+        id
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+
+    for c in text.chars() {
+        match c {
+            '"' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
@@ -0,0 +1,321 @@
+//! Proofs are exported as nested JSON objects, one per step of the
+//! derivation, following the schema in `proof.schema.json`:
+//!
+//! ```json
+//! {
+//!   "rule": "S-Trans",
+//!   "conclusion": "a <: c",
+//!   "bindings": { "x": "a", "y": "b", "z": "c" },
+//!   "premises": [ ... ]
+//! }
+//! ```
+//!
+//! `conclusion` has every known variable substituted and is written in the
+//! same syntax the parser accepts. Variables left unbound are named `$_0`,
+//! `$_1` and so on, consistently across the whole proof, so that variables
+//! from different rules never share a name.
+//!
+//! `bindings` is only present when requested through `JsonOptions`, and
+//! lists the variables of the rule applied at that step. Built-in steps
+//! share their parent's variables and so have no bindings of their own.
+//!
+//! `ProofTree::from_json` reads the same format back, ignoring `bindings`, so
+//! that proofs from disk or from other tools can be passed to
+//! `RuleSet::check_tree`.
+
+use super::{Proof, ProofTree};
+use crate::expr::{parse, Cursor, Expr, ParseError, Variable};
+use crate::state::State;
+use std::fmt::Write;
+use std::rc::Rc;
+
+pub const JSON_SCHEMA: &str = include_str!("proof.schema.json");
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct JsonOptions {
+    pub bindings: bool,
+}
+
+impl Proof {
+    pub fn to_json(&self) -> String {
+        self.to_json_with(&JsonOptions::default())
+    }
+
+    pub fn to_json_with(&self, options: &JsonOptions) -> String {
+        let mut writer = Writer {
+            options,
+            state: &self.state,
+            names: Vec::new(),
+            output: String::new(),
+        };
+
+        writer.proof(self, None);
+        writer.output
+    }
+}
+
+struct Writer<'a> {
+    options: &'a JsonOptions,
+    state: &'a State,
+    names: Vec<(usize, Rc<Variable>)>,
+    output: String,
+}
+
+impl Writer<'_> {
+    fn proof(&mut self, proof: &Proof, parent: Option<usize>) {
+        let (scope, conclusion) = &proof.conclusion;
+        let conclusion = self.state.rename((*scope, conclusion), &mut self.names);
+
+        self.output.push_str("{\"rule\":");
+        write_string(&mut self.output, &proof.rule);
+        self.output.push_str(",\"conclusion\":");
+        write_string(&mut self.output, &conclusion.to_string());
+
+        if self.options.bindings {
+            self.output.push_str(",\"bindings\":{");
+
+            if parent != Some(*scope) {
+                for (i, (var, _)) in self.state.bindings(*scope).iter().enumerate() {
+                    let value = Expr::Var(var.clone());
+                    let value = self.state.rename((*scope, &value), &mut self.names);
+
+                    if i > 0 {
+                        self.output.push(',');
+                    }
+                    write_string(&mut self.output, &var.0);
+                    self.output.push(':');
+                    write_string(&mut self.output, &value.to_string());
+                }
+            }
+
+            self.output.push('}');
+        }
+
+        self.output.push_str(",\"premises\":[");
+
+        for (i, premise) in proof.parents.iter().enumerate() {
+            if i > 0 {
+                self.output.push(',');
+            }
+            self.proof(premise, Some(*scope));
+        }
+
+        self.output.push_str("]}");
+    }
+}
+
+fn write_string(output: &mut String, text: &str) {
+    output.push('"');
+
+    for c in text.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if c.is_control() => {
+                let mut units = [0; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    write!(output, "\\u{:04x}", unit).unwrap();
+                }
+            }
+            _ => output.push(c),
+        }
+    }
+
+    output.push('"');
+}
+
+impl ProofTree {
+    pub fn from_json(input: &str) -> Result<ProofTree, ParseError> {
+        let mut reader = Reader {
+            cursor: Cursor::at(input, 1, 1),
+        };
+
+        let tree = reader.proof()?;
+
+        if reader.cursor.is_done() {
+            Ok(tree)
+        } else {
+            Err(reader.cursor.unexpected("end of input"))
+        }
+    }
+}
+
+struct Reader<'a> {
+    cursor: Cursor<'a>,
+}
+
+impl Reader<'_> {
+    fn proof(&mut self) -> Result<ProofTree, ParseError> {
+        let (mut rule, mut conclusion, mut premises) = (None, None, None);
+        let (line, column) = self.cursor.position();
+
+        self.cursor.expect('{')?;
+
+        if !self.cursor.skip('}') {
+            loop {
+                let (key_line, key_column) = self.cursor.position();
+                let key = self.string()?;
+                self.cursor.expect(':')?;
+
+                match key.as_str() {
+                    "rule" => rule = Some(self.string()?),
+                    "conclusion" => conclusion = Some(self.conclusion()?),
+                    "bindings" => self.bindings()?,
+                    "premises" => premises = Some(self.premises()?),
+                    _ => {
+                        return Err(ParseError {
+                            line: key_line,
+                            column: key_column,
+                            message: format!("unexpected key \"{}\"", key),
+                        })
+                    }
+                }
+
+                if !self.cursor.skip(',') {
+                    self.cursor.expect('}')?;
+                    break;
+                }
+            }
+        }
+
+        match (rule, conclusion, premises) {
+            (Some(rule), Some(conclusion), Some(premises)) => Ok(ProofTree {
+                rule,
+                conclusion,
+                premises,
+            }),
+            _ => Err(ParseError {
+                line,
+                column,
+                message: String::from("expected \"rule\", \"conclusion\" and \"premises\""),
+            }),
+        }
+    }
+
+    fn conclusion(&mut self) -> Result<Expr, ParseError> {
+        let (line, column) = self.cursor.position();
+        let text = self.string()?;
+
+        parse(&text).map_err(|err| ParseError {
+            line,
+            column,
+            message: format!("invalid conclusion: {}", err.message),
+        })
+    }
+
+    fn bindings(&mut self) -> Result<(), ParseError> {
+        self.cursor.expect('{')?;
+
+        if !self.cursor.skip('}') {
+            loop {
+                self.string()?;
+                self.cursor.expect(':')?;
+                self.string()?;
+
+                if !self.cursor.skip(',') {
+                    return self.cursor.expect('}');
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn premises(&mut self) -> Result<Vec<ProofTree>, ParseError> {
+        let mut premises = Vec::new();
+
+        self.cursor.expect('[')?;
+
+        if !self.cursor.skip(']') {
+            loop {
+                premises.push(self.proof()?);
+
+                if !self.cursor.skip(',') {
+                    self.cursor.expect(']')?;
+                    break;
+                }
+            }
+        }
+
+        Ok(premises)
+    }
+
+    fn string(&mut self) -> Result<String, ParseError> {
+        let mut text = String::new();
+
+        self.cursor.expect('"')?;
+
+        loop {
+            match self.cursor.peek() {
+                Some('"') => break,
+                Some('\\') => text.push(self.escape()?),
+                Some(&c) if !c.is_control() => {
+                    text.push(c);
+                    self.cursor.advance();
+                }
+                _ => return Err(self.cursor.unexpected("'\"'")),
+            }
+        }
+        self.cursor.advance();
+
+        Ok(text)
+    }
+
+    fn escape(&mut self) -> Result<char, ParseError> {
+        let (line, column) = self.cursor.location();
+        self.cursor.advance();
+
+        let c = match self.cursor.peek() {
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('/') => '/',
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('u') => {
+                self.cursor.advance();
+                let mut units = vec![self.code_unit()?];
+
+                if (0xd800..0xdc00).contains(&units[0]) && self.cursor.peek() == Some(&'\\') {
+                    self.cursor.advance();
+                    self.cursor.exact('u')?;
+                    units.push(self.code_unit()?);
+                }
+
+                let mut chars = std::char::decode_utf16(units.iter().cloned());
+
+                return match (chars.next(), chars.next()) {
+                    (Some(Ok(c)), None) => Ok(c),
+                    _ => Err(ParseError {
+                        line,
+                        column,
+                        message: String::from("invalid unicode escape"),
+                    }),
+                };
+            }
+            _ => return Err(self.cursor.unexpected("an escape sequence")),
+        };
+
+        self.cursor.advance();
+        Ok(c)
+    }
+
+    fn code_unit(&mut self) -> Result<u16, ParseError> {
+        let mut code = 0;
+
+        for _ in 0..4 {
+            match self.cursor.peek().and_then(|c| c.to_digit(16)) {
+                Some(digit) => code = code * 16 + digit as u16,
+                None => return Err(self.cursor.unexpected("a hexadecimal digit")),
+            }
+            self.cursor.advance();
+        }
+
+        Ok(code)
+    }
+}
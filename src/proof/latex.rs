@@ -0,0 +1,221 @@
+use super::Proof;
+use crate::expr::*;
+
+const MAX_BUSSPROOFS_PREMISES: usize = 5;
+
+const INFERENCES: [&str; MAX_BUSSPROOFS_PREMISES] = [
+    "UnaryInfC",
+    "BinaryInfC",
+    "TrinaryInfC",
+    "QuaternaryInfC",
+    "QuinaryInfC",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LatexStyle {
+    Bussproofs,
+    Mathpartir,
+}
+
+#[derive(Clone, Copy)]
+pub struct LatexOptions {
+    pub style: LatexStyle,
+    pub rule: fn(&str) -> String,
+    pub word: fn(&str) -> String,
+    pub variable: fn(&str) -> String,
+}
+
+impl Default for LatexOptions {
+    fn default() -> LatexOptions {
+        LatexOptions {
+            style: LatexStyle::Bussproofs,
+            rule: |name| format!("\\textsc{{{}}}", escape_text(name)),
+            word: |name| {
+                if name.chars().all(char::is_alphanumeric) {
+                    format!("\\textsf{{{}}}", escape_text(name))
+                } else {
+                    escape_math(name)
+                }
+            },
+            variable: |name| format!("\\textit{{{}}}", escape_text(name)),
+        }
+    }
+}
+
+pub fn escape_text(text: &str) -> String {
+    let mut escaped = String::new();
+
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '{' | '}' | '$' | '&' | '#' | '_' | '%' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+pub fn escape_math(text: &str) -> String {
+    let mut escaped = String::new();
+
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\backslash "),
+            '^' => escaped.push_str("\\hat{}"),
+            '~' => escaped.push_str("\\sim "),
+            '{' | '}' | '$' | '&' | '#' | '_' | '%' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+impl Proof {
+    pub fn to_latex(&self) -> String {
+        self.to_latex_with(&LatexOptions::default())
+    }
+
+    pub fn to_latex_with(&self, options: &LatexOptions) -> String {
+        let mut latex = Latex {
+            options,
+            output: String::new(),
+        };
+
+        match options.style {
+            LatexStyle::Bussproofs if self.max_premises() <= MAX_BUSSPROOFS_PREMISES => {
+                latex.bussproofs(self)
+            }
+            _ => latex.mathpartir(self),
+        }
+
+        latex.output
+    }
+
+    fn max_premises(&self) -> usize {
+        let parents = self.parents.iter().map(|parent| parent.max_premises());
+        parents.fold(self.parents.len(), usize::max)
+    }
+}
+
+struct Latex<'a> {
+    options: &'a LatexOptions,
+    output: String,
+}
+
+impl Latex<'_> {
+    fn bussproofs(&mut self, proof: &Proof) {
+        self.output.push_str("\\begin{prooftree}\n");
+        self.bussproofs_step(proof);
+        self.output.push_str("\\end{prooftree}\n");
+    }
+
+    fn bussproofs_step(&mut self, proof: &Proof) {
+        if proof.parents.is_empty() {
+            self.output.push_str("\\AxiomC{}\n");
+        }
+
+        for parent in &proof.parents {
+            self.bussproofs_step(parent);
+        }
+
+        let count = proof.parents.len().max(1);
+        let label = (self.options.rule)(&proof.rule);
+        let conclusion = self.expr(&proof.conclusion());
+
+        self.output
+            .push_str(&format!("\\RightLabel{{\\scriptsize {}}}\n", label));
+        self.output.push_str(&format!(
+            "\\{}{{${}$}}\n",
+            INFERENCES[count - 1],
+            conclusion
+        ));
+    }
+
+    fn mathpartir(&mut self, proof: &Proof) {
+        self.output.push_str("\\begin{mathpar}\n");
+        self.mathpartir_step(proof, 1);
+        self.output.push_str("\n\\end{mathpar}\n");
+    }
+
+    fn mathpartir_step(&mut self, proof: &Proof, level: usize) {
+        let label = (self.options.rule)(&proof.rule);
+        let indent = "  ".repeat(level);
+
+        self.output
+            .push_str(&format!("{}\\inferrule*[right={}]\n", indent, label));
+        self.output.push_str(&format!("{}{{", indent));
+
+        if proof.parents.is_empty() {
+            self.output.push(' ');
+        }
+
+        for (i, parent) in proof.parents.iter().enumerate() {
+            self.output.push_str(if i == 0 { "\n" } else { " \\\\\n" });
+            self.mathpartir_step(parent, level + 1);
+        }
+
+        if !proof.parents.is_empty() {
+            self.output.push_str(&format!("\n{}", indent));
+        }
+
+        let conclusion = self.expr(&proof.conclusion());
+        self.output
+            .push_str(&format!("}}\n{}{{{}}}", indent, conclusion));
+    }
+
+    fn expr(&self, expr: &Expr) -> String {
+        match expr {
+            Expr::Var(var) => (self.options.variable)(&var.0),
+            Expr::Wrd(wrd) => (self.options.word)(&wrd.0),
+            Expr::Num(num) => num.0.to_string(),
+            Expr::Str(txt) => format!("\\texttt{{\"{}\"}}", escape_text(&txt.0)),
+            Expr::Seq(seq) if seq.0.len() < 2 => format!("({})", self.items(seq)),
+            Expr::Seq(seq) => self.items(seq),
+            Expr::Lst(lst) => self.list(lst),
+        }
+    }
+
+    fn items(&self, seq: &Sequence) -> String {
+        let items = seq.0.iter().map(|item| match item {
+            Expr::Seq(seq) => format!("({})", self.items(seq)),
+            _ => self.expr(item),
+        });
+        items.collect::<Vec<_>>().join("\\ ")
+    }
+
+    fn list(&self, lst: &List) -> String {
+        let mut items = Vec::new();
+        let mut list = lst;
+
+        while let Some(Pair { head, tail }) = &list.pair {
+            items.push(self.expr(head));
+
+            match tail {
+                Expr::Lst(next) if next.tag == lst.tag => list = next,
+                _ => {
+                    let last = items.pop().unwrap_or_default();
+                    items.push(format!("{} \\mid {}", last, self.expr(tail)));
+                    break;
+                }
+            }
+        }
+
+        let tag = if lst.tag.is_empty() {
+            String::new()
+        } else {
+            (self.options.word)(&lst.tag)
+        };
+
+        format!("{}[{}]", tag, items.join(", "))
+    }
+}
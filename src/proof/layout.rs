@@ -1,78 +1,48 @@
-use crate::expr::Expr;
-use crate::state::State;
-use im::vector::Vector;
+use super::Proof;
 use std::cmp;
 use std::fmt;
-use std::rc::Rc;
+use std::mem;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthChar;
 
-pub struct Proof {
-    rule: String,
-    state: State,
-    parents: Vector<Rc<Proof>>,
-    conclusion: (usize, Expr),
+const DIVIDER: char = '\u{2500}';
+const PADDING: usize = 3;
+const INDENT: usize = 4;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RenderOptions {
+    pub max_width: Option<usize>,
 }
 
 impl Proof {
-    pub fn new(
-        rule: &str,
-        state: &State,
-        proofs: Vector<Rc<Proof>>,
-        (scope, conclusion): (usize, &Expr),
-    ) -> Proof {
-        Proof {
-            rule: String::from(rule),
-            state: state.clone(),
-            parents: proofs,
-            conclusion: (scope, conclusion.clone()),
+    pub fn render_with(&self, options: &RenderOptions) -> String {
+        match options.max_width {
+            Some(width) => format!("{:width$}", self, width = width),
+            None => self.to_string(),
         }
     }
-
-    fn conclusion(&self) -> Expr {
-        let (scope, expr) = &self.conclusion;
-        self.state.resolve_scoped(expr, *scope)
-    }
-}
-
-impl fmt::Debug for Proof {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        display_nested(self, f, 1)
-    }
 }
 
-fn display_nested(proof: &Proof, f: &mut fmt::Formatter, level: usize) -> fmt::Result {
-    let indent = " ".repeat(4 * level);
-    writeln!(f, "{}[{}] {}", indent, proof.rule, proof.conclusion())?;
-    for parent in proof.parents.iter() {
-        display_nested(parent, f, level + 1)?;
-    }
-    Ok(())
-}
-
-impl fmt::Display for Proof {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut layout = Layout::new(self);
-        layout.render(f)
-    }
-}
-
-const DIVIDER: char = '\u{2500}';
-const PADDING: usize = 3;
-
-struct Layout<'a> {
-    proof: &'a Proof,
-    parents: Vec<Layout<'a>>,
+pub struct Layout {
+    rule: String,
+    text: String,
+    parents: Vec<Layout>,
     premise_indent: usize,
     divider: Bounds,
     conclusion: Bounds,
     region: Bounds,
 }
 
-impl Layout<'_> {
-    fn new(proof: &Proof) -> Layout<'_> {
+impl Layout {
+    pub fn new(proof: &Proof) -> Layout {
         let parents = proof.parents.iter().map(|t| Layout::new(t)).collect();
+        Layout::node(&proof.rule, proof.conclusion().to_string(), parents)
+    }
 
+    fn node(rule: &str, text: String, parents: Vec<Layout>) -> Layout {
         Layout {
-            proof,
+            rule: String::from(rule),
+            text,
             parents,
             premise_indent: 0,
             divider: Bounds::default(),
@@ -81,15 +51,67 @@ impl Layout<'_> {
         }
     }
 
-    fn render(&mut self, f: &mut fmt::Formatter) -> fmt::Result {
+    pub fn render(self, f: &mut fmt::Formatter, max_width: Option<usize>) -> fmt::Result {
+        let mut blocks = vec![self];
+        let mut i = 0;
+
+        while i < blocks.len() {
+            let plan = loop {
+                let plan = blocks[i].plan();
+
+                if max_width.map_or(true, |max| plan.width() <= max) {
+                    break plan;
+                }
+
+                if plan.width() <= blocks[i].width() {
+                    break plan;
+                }
+
+                let reference = blocks.len();
+
+                match blocks[i].elide(reference) {
+                    Some(block) => blocks.push(block),
+                    None => break plan,
+                }
+            };
+
+            if i > 0 {
+                writeln!(f, "\n({})", i)?;
+            }
+            plan.render(f)?;
+            i += 1;
+        }
+
+        Ok(())
+    }
+
+    fn plan(&mut self) -> Plan {
         let mut plan = Plan::default();
         self.prepare(0);
         self.generate(&mut plan, 1, 0);
-        plan.render(f)
+        plan
     }
 
-    fn conclusion(&self) -> String {
-        format!("{}", self.proof.conclusion())
+    fn width(&self) -> usize {
+        let rule_width = display_width(&self.rule) + 1;
+        let width = cmp::max(self.conclusion.right, self.divider.right + rule_width);
+        INDENT + width
+    }
+
+    fn elide(&mut self, reference: usize) -> Option<Layout> {
+        let widest = self.parents.iter_mut().max_by_key(|layout| {
+            let subproofs = layout.parents.len();
+            (subproofs > 0, layout.region.right - layout.region.left)
+        })?;
+
+        if widest.parents.is_empty() {
+            return None;
+        }
+
+        let label = format!("({})", reference);
+        let placeholder = Layout::node(&label, widest.text.clone(), Vec::new());
+
+        Some(mem::replace(widest, placeholder))
     }
 
     fn prepare(&mut self, offset: usize) {
@@ -108,11 +130,13 @@ impl Layout<'_> {
             premise_width = last.conclusion.right - premise_left;
         }
 
-        let rule_width = self.proof.rule.chars().count() + 1;
-        let conc_width = self.conclusion().chars().count();
+        let rule_width = display_width(&self.rule) + 1;
+        let conc_width = display_width(&self.text);
         let divider_width = cmp::max(conc_width, premise_width);
         let conc_indent = (divider_width - conc_width) / 2;
 
+        self.premise_indent = 0;
+
         if premise_width < conc_width {
             let premise_offset = 2 * (premise_left - offset);
             let premise_indent = conc_width - premise_width;
@@ -141,11 +165,11 @@ impl Layout<'_> {
             layout.generate(plan, depth + 2, indent + self.premise_indent);
         }
 
-        let divider = format!("{} {}", self.divider.repeat(DIVIDER), self.proof.rule);
+        let divider = format!("{} {}", self.divider.repeat(DIVIDER), self.rule);
         let indent = indent / 2;
 
         plan.write(depth + 1, indent + self.divider.left, &divider);
-        plan.write(depth, indent + self.conclusion.left, &self.conclusion());
+        plan.write(depth, indent + self.conclusion.left, &self.text);
     }
 }
 
@@ -179,15 +203,30 @@ impl Plan {
         }
 
         if let Some(line) = self.lines.get_mut(depth - 1) {
-            line.push_str(&" ".repeat(offset - line.chars().count()));
+            line.push_str(&" ".repeat(offset - display_width(line)));
             line.push_str(text);
         }
     }
 
+    fn width(&self) -> usize {
+        let widths = self.lines.iter().map(|line| display_width(line));
+        INDENT + widths.max().unwrap_or(0)
+    }
+
     fn render(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let indent = " ".repeat(INDENT);
+
         for line in self.lines.iter().rev() {
-            writeln!(f, "    {}", line)?;
+            writeln!(f, "{}{}", indent, line)?;
         }
         Ok(())
     }
 }
+
+fn display_width(text: &str) -> usize {
+    let graphemes = text.graphemes(true).map(|grapheme| {
+        let widths = grapheme.chars().map(|c| c.width().unwrap_or(0));
+        widths.max().unwrap_or(0)
+    });
+    graphemes.sum()
+}
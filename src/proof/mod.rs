@@ -0,0 +1,88 @@
+mod dot;
+mod json;
+mod latex;
+mod layout;
+mod tests;
+mod tree;
+mod visit;
+
+pub use self::dot::DotOptions;
+pub use self::json::{JsonOptions, JSON_SCHEMA};
+pub use self::latex::{escape_math, escape_text, LatexOptions, LatexStyle};
+pub use self::layout::RenderOptions;
+pub use self::tree::ProofTree;
+pub use self::visit::Visitor;
+
+use self::layout::Layout;
+use crate::expr::Expr;
+use crate::state::State;
+use im::vector::Vector;
+use std::fmt;
+use std::rc::Rc;
+
+pub struct Proof {
+    rule: String,
+    state: State,
+    parents: Vector<Rc<Proof>>,
+    conclusion: (usize, Expr),
+}
+
+impl Proof {
+    pub fn new(
+        rule: &str,
+        state: &State,
+        proofs: Vector<Rc<Proof>>,
+        (scope, conclusion): (usize, &Expr),
+    ) -> Proof {
+        Proof {
+            rule: String::from(rule),
+            state: state.clone(),
+            parents: proofs,
+            conclusion: (scope, conclusion.clone()),
+        }
+    }
+
+    pub fn rule(&self) -> &str {
+        &self.rule
+    }
+
+    pub fn conclusion(&self) -> Expr {
+        let (scope, expr) = &self.conclusion;
+        self.state.resolve_scoped(expr, *scope)
+    }
+
+    pub fn premises(&self) -> impl Iterator<Item = &Rc<Proof>> {
+        self.parents.iter()
+    }
+
+    pub fn depth(&self) -> usize {
+        let parents = self.parents.iter().map(|parent| parent.depth());
+        parents.max().unwrap_or(0) + 1
+    }
+
+    pub fn size(&self) -> usize {
+        let parents = self.parents.iter().map(|parent| parent.size());
+        parents.sum::<usize>() + 1
+    }
+}
+
+impl fmt::Debug for Proof {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        display_nested(self, f, 1)
+    }
+}
+
+fn display_nested(proof: &Proof, f: &mut fmt::Formatter, level: usize) -> fmt::Result {
+    let indent = " ".repeat(4 * level);
+    writeln!(f, "{}[{}] {}", indent, proof.rule, proof.conclusion())?;
+    for parent in proof.parents.iter() {
+        display_nested(parent, f, level + 1)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for Proof {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Layout::new(self).render(f, f.width())
+    }
+}
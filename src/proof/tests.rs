@@ -0,0 +1,471 @@
+#![cfg(test)]
+
+use crate::expr::*;
+use crate::lang::RuleSet;
+use crate::proof::*;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+fn derive_one(source: &str, query: &str) -> Rc<Proof> {
+    let mut rules = RuleSet::new();
+    rules.load_str(source).unwrap();
+
+    let (_, proof) = rules.derive(&parse(query).unwrap()).next().unwrap();
+    proof
+}
+
+fn subtype_proof() -> Rc<Proof> {
+    derive_one(
+        "
+        rule S-AB
+            a <: b
+        rule S-BC
+            b <: c
+        rule S_Trans
+            $x <: $z :- $x <: $y, $y <: $z
+        ",
+        "a <: c",
+    )
+}
+
+#[test]
+fn render_bussproofs() {
+    assert_eq!(
+        subtype_proof().to_latex(),
+        "\\begin{prooftree}
+\\AxiomC{}
+\\RightLabel{\\scriptsize \\textsc{S-AB}}
+\\UnaryInfC{$\\textsf{a}\\ <:\\ \\textsf{b}$}
+\\AxiomC{}
+\\RightLabel{\\scriptsize \\textsc{S-BC}}
+\\UnaryInfC{$\\textsf{b}\\ <:\\ \\textsf{c}$}
+\\RightLabel{\\scriptsize \\textsc{S\\_Trans}}
+\\BinaryInfC{$\\textsf{a}\\ <:\\ \\textsf{c}$}
+\\end{prooftree}
+"
+    );
+}
+
+#[test]
+fn render_mathpartir() {
+    let options = LatexOptions {
+        style: LatexStyle::Mathpartir,
+        ..LatexOptions::default()
+    };
+
+    assert_eq!(
+        subtype_proof().to_latex_with(&options),
+        "\\begin{mathpar}
+  \\inferrule*[right=\\textsc{S\\_Trans}]
+  {
+    \\inferrule*[right=\\textsc{S-AB}]
+    { }
+    {\\textsf{a}\\ <:\\ \\textsf{b}} \\\\
+    \\inferrule*[right=\\textsc{S-BC}]
+    { }
+    {\\textsf{b}\\ <:\\ \\textsf{c}}
+  }
+  {\\textsf{a}\\ <:\\ \\textsf{c}}
+\\end{mathpar}
+"
+    );
+}
+
+#[test]
+fn render_latex_with_custom_escaping() {
+    let options = LatexOptions {
+        rule: |name| name.to_lowercase(),
+        word: |name| format!("\\mathtt{{{}}}", escape_math(name)),
+        variable: |name| name.to_uppercase(),
+        ..LatexOptions::default()
+    };
+
+    let proof = derive_one("rule Refl\n    $x_1 = $x_1\n", "($v a) = $w");
+
+    assert_eq!(
+        proof.to_latex_with(&options),
+        "\\begin{prooftree}
+\\AxiomC{}
+\\RightLabel{\\scriptsize refl}
+\\UnaryInfC{$(V\\ \\mathtt{a})\\ \\mathtt{=}\\ (V\\ \\mathtt{a})$}
+\\end{prooftree}
+"
+    );
+}
+
+#[test]
+fn render_lists_numbers_and_strings_in_latex() {
+    let proof = derive_one(
+        "rule show\n    show k[1, \"a_b\" | $t] {x}\n",
+        "show k[1, \"a_b\" | []] $y",
+    );
+
+    assert!(proof
+        .to_latex()
+        .contains("$\\textsf{show}\\ \\textsf{k}[1, \\texttt{\"a\\_b\"} \\mid []]\\ \\{x\\}$"));
+}
+
+#[test]
+fn fall_back_to_mathpartir_for_many_premises() {
+    let proof = derive_one(
+        "
+        rule many
+            p :- q, q, q, q, q, q
+        rule q
+            q
+        ",
+        "p",
+    );
+
+    assert!(proof.to_latex().starts_with("\\begin{mathpar}\n"));
+}
+
+#[test]
+fn export_json() {
+    assert_eq!(
+        subtype_proof().to_json(),
+        r#"{"rule":"S_Trans","conclusion":"a <: c","premises":[{"rule":"S-AB","conclusion":"a <: b","premises":[]},{"rule":"S-BC","conclusion":"b <: c","premises":[]}]}"#
+    );
+}
+
+#[test]
+fn export_json_with_bindings() {
+    let options = JsonOptions { bindings: true };
+
+    assert_eq!(
+        subtype_proof().to_json_with(&options),
+        r#"{"rule":"S_Trans","conclusion":"a <: c","bindings":{"x":"a","y":"b","z":"c"},"premises":[{"rule":"S-AB","conclusion":"a <: b","bindings":{},"premises":[]},{"rule":"S-BC","conclusion":"b <: c","bindings":{},"premises":[]}]}"#
+    );
+}
+
+#[test]
+fn export_json_without_bindings_for_builtins() {
+    let proof = derive_one(
+        "
+        rule diff
+            diff $x $y :- $x != $y
+        ",
+        "diff a b",
+    );
+
+    assert_eq!(
+        proof.to_json_with(&JsonOptions { bindings: true }),
+        r#"{"rule":"diff","conclusion":"diff a b","bindings":{"x":"a","y":"b"},"premises":[{"rule":"!=","conclusion":"a != b","bindings":{},"premises":[]}]}"#
+    );
+}
+
+#[test]
+fn export_json_with_variables_from_different_rules() {
+    let mut rules = RuleSet::new();
+    rules
+        .load_str(
+            "
+            rule any
+                any $z
+            rule two
+                two $a $b :- any $a, any $b, $a != $b
+            ",
+        )
+        .unwrap();
+
+    let (_, proof) = rules.derive(&parse("two $p $q").unwrap()).next().unwrap();
+
+    assert_eq!(
+        proof.to_json_with(&JsonOptions { bindings: true }),
+        r#"{"rule":"two","conclusion":"two $_0 $_1","bindings":{"a":"$_0","b":"$_1"},"premises":[{"rule":"any","conclusion":"any $_0","bindings":{},"premises":[]},{"rule":"any","conclusion":"any $_1","bindings":{},"premises":[]},{"rule":"!=","conclusion":"$_0 != $_1","bindings":{},"premises":[]}]}"#
+    );
+
+    let tree = ProofTree::from_json(&proof.to_json()).unwrap();
+    assert_eq!(rules.check_tree(&tree), Ok(()));
+}
+
+#[test]
+fn escape_strings_in_json() {
+    let proof = derive_one(
+        r#"
+        rule quote
+            quote "say \"hi\"\n"
+        "#,
+        "quote $x",
+    );
+
+    assert_eq!(
+        proof.to_json(),
+        r#"{"rule":"quote","conclusion":"quote \"say \\\"hi\\\"\\n\"","premises":[]}"#
+    );
+}
+
+#[test]
+fn export_dot() {
+    assert_eq!(
+        subtype_proof().to_dot(),
+        "digraph proof {
+  rankdir=BT;
+  node [shape=box];
+  n0 [label=\"S-AB\\na <: b\"];
+  n1 [label=\"S-BC\\nb <: c\"];
+  n2 [label=\"S_Trans\\na <: c\"];
+  n2 -> n0;
+  n2 -> n1;
+}
+"
+    );
+}
+
+fn repeated_proof() -> Rc<Proof> {
+    derive_one(
+        r#"
+        rule leaf
+            ok "x"
+        rule pair
+            pair $x :- ok $x, ok $x
+        rule both
+            both $x :- pair $x, pair $x
+        "#,
+        "both $x",
+    )
+}
+
+#[test]
+fn export_dot_without_merging() {
+    let dot = repeated_proof().to_dot();
+
+    assert_eq!(dot.matches("[label=\"leaf").count(), 4);
+    assert_eq!(dot.matches("[label=\"pair").count(), 2);
+    assert!(dot.contains("  n6 [label=\"both\\nboth \\\"x\\\"\"];\n"));
+}
+
+#[test]
+fn merge_identical_subproofs_in_dot() {
+    let options = DotOptions {
+        merge_identical: true,
+    };
+
+    assert_eq!(
+        repeated_proof().to_dot_with(&options),
+        "digraph proof {
+  rankdir=BT;
+  node [shape=box];
+  n0 [label=\"leaf\\nok \\\"x\\\"\"];
+  n1 [label=\"pair\\npair \\\"x\\\"\"];
+  n1 -> n0;
+  n1 -> n0;
+  n2 [label=\"both\\nboth \\\"x\\\"\"];
+  n2 -> n1;
+  n2 -> n1;
+}
+"
+    );
+}
+
+#[test]
+fn read_proof_structure() {
+    let proof = subtype_proof();
+
+    assert_eq!(proof.rule(), "S_Trans");
+    assert_eq!(proof.conclusion(), parse("a <: c").unwrap());
+    assert_eq!(proof.depth(), 2);
+    assert_eq!(proof.size(), 3);
+
+    let premises: Vec<_> = proof.premises().map(|p| p.conclusion()).collect();
+    assert_eq!(
+        premises,
+        vec![parse("a <: b").unwrap(), parse("b <: c").unwrap()]
+    );
+}
+
+#[test]
+fn count_rule_usage_with_a_visitor() {
+    #[derive(Default)]
+    struct RuleCounter(HashMap<String, usize>, usize);
+
+    impl Visitor for RuleCounter {
+        fn enter(&mut self, proof: &Proof) -> bool {
+            *self.0.entry(proof.rule().to_string()).or_default() += 1;
+            proof.rule() != "pair"
+        }
+
+        fn leave(&mut self, _: &Proof) {
+            self.1 += 1;
+        }
+    }
+
+    let mut counter = RuleCounter::default();
+    repeated_proof().visit(&mut counter);
+
+    assert_eq!(counter.0.get("both"), Some(&1));
+    assert_eq!(counter.0.get("pair"), Some(&2));
+    assert_eq!(counter.0.get("leaf"), None);
+    assert_eq!(counter.1, 3);
+}
+
+#[test]
+fn read_proof_trees_from_json() {
+    let proof = subtype_proof();
+    let options = JsonOptions { bindings: true };
+
+    for json in &[proof.to_json(), proof.to_json_with(&options)] {
+        assert_eq!(ProofTree::from_json(json), Ok(proof.to_tree()));
+    }
+}
+
+#[test]
+fn read_json_with_words_that_look_like_numbers() {
+    let n = |digit: &str| {
+        let word = |name: &str| Expr::Wrd(Rc::new(Word(String::from(name))));
+        Expr::Seq(Rc::new(Sequence(vec![word("n"), word(digit)])))
+    };
+
+    let mut rules = RuleSet::new();
+    rules.insert("n-1", &n("1"), &[]);
+    rules.insert("n-2", &n("2"), &[n("1")]);
+
+    let (_, proof) = rules.derive(&n("2")).next().unwrap();
+    let tree = ProofTree::from_json(&proof.to_json()).unwrap();
+
+    assert_eq!(tree, proof.to_tree());
+    assert_eq!(rules.check_tree(&tree), Ok(()));
+}
+
+#[test]
+fn read_json_with_whitespace_and_escapes() {
+    let json = r#"
+        {
+          "rule": "quote\u0021",
+          "conclusion": "quote \"say \\\"hi\\\"\\n\"",
+          "premises": [ ]
+        }
+    "#;
+
+    assert_eq!(
+        ProofTree::from_json(json),
+        Ok(ProofTree {
+            rule: String::from("quote!"),
+            conclusion: parse("quote \"say \\\"hi\\\"\\n\"").unwrap(),
+            premises: vec![],
+        })
+    );
+}
+
+#[test]
+fn report_errors_in_json_proofs() {
+    let error = |json: &str| ProofTree::from_json(json).unwrap_err().to_string();
+
+    assert_eq!(
+        error(r#"{"rule": "r", "premises": []}"#),
+        "1:1: expected \"rule\", \"conclusion\" and \"premises\""
+    );
+    assert_eq!(
+        error("{\"rule\": \"r\",\n \"size\": 1}"),
+        "2:2: unexpected key \"size\""
+    );
+    assert_eq!(
+        error(r#"{"rule": "r", "conclusion": "a (b", "premises": []}"#),
+        "1:29: invalid conclusion: expected ')', found end of input"
+    );
+    assert_eq!(
+        error(r#"{"rule": "r", "conclusion": "a", "premises": [] } x"#),
+        "1:51: expected end of input, found 'x'"
+    );
+}
+
+fn chain_proof() -> Rc<Proof> {
+    derive_one(
+        "
+        rule S-AB
+            alpha <: beta
+        rule S-BC
+            beta <: gamma
+        rule S-CD
+            gamma <: delta
+        rule S-Trans
+            $x <: $z :- $x <: $y, $y <: $z
+        ",
+        "alpha <: delta",
+    )
+}
+
+#[test]
+fn render_proof_side_by_side() {
+    assert_eq!(
+        chain_proof().to_string(),
+        "                         ───────────── S-BC   ────────────── S-CD
+                         beta <: gamma        gamma <: delta
+    ───────────── S-AB   ─────────────────────────────────── S-Trans
+    alpha <: beta                   beta <: delta
+    ───────────────────────────────────────────── S-Trans
+                   alpha <: delta
+"
+    );
+}
+
+#[test]
+fn elide_subproofs_wider_than_the_limit() {
+    let expected = "    ───────────── S-AB   ───────────── (1)
+    alpha <: beta        beta <: delta
+    ────────────────────────────────── S-Trans
+              alpha <: delta
+
+(1)
+    ───────────── S-BC   ────────────── S-CD
+    beta <: gamma        gamma <: delta
+    ─────────────────────────────────── S-Trans
+               beta <: delta
+";
+
+    let proof = chain_proof();
+    let options = RenderOptions {
+        max_width: Some(50),
+    };
+
+    assert_eq!(format!("{:50}", proof), expected);
+    assert_eq!(proof.render_with(&options), expected);
+    assert_eq!(format!("{:70}", proof), proof.to_string());
+}
+
+#[test]
+fn overflow_the_limit_when_nothing_can_be_elided() {
+    let proof = subtype_proof();
+    assert_eq!(format!("{:10}", proof), proof.to_string());
+}
+
+#[test]
+fn stop_eliding_when_the_conclusion_is_wider_than_the_limit() {
+    let proof = derive_one(
+        "
+        rule long
+            a-conclusion-too-wide-for-the-limit $x :- p $x
+        rule p
+            p $x :- q $x
+        rule q
+            q a
+        ",
+        "a-conclusion-too-wide-for-the-limit $y",
+    );
+
+    assert_eq!(format!("{:40}", proof), proof.to_string());
+}
+
+#[test]
+fn align_proofs_by_display_width() {
+    let proof = derive_one(
+        "
+        rule T-漢字
+            Γ ⊢ 漢字 : type
+        rule T-Café
+            Γ ⊢ cafe\u{301} : 👩‍🔬
+        rule T-Both
+            Γ ⊢ (λ $x) : ok :- Γ ⊢ $x : type, Γ ⊢ cafe\u{301} : $y
+        ",
+        "Γ ⊢ (λ 漢字) : ok",
+    );
+
+    assert_eq!(
+        proof.to_string(),
+        "    ─────────────── T-漢字   ───────────── T-Café
+    Γ ⊢ 漢字 : type          Γ ⊢ cafe\u{301} : 👩‍🔬
+    ────────────────────────────────────── T-Both
+              Γ ⊢ (λ 漢字) : ok
+"
+    );
+}
@@ -0,0 +1,47 @@
+use super::Proof;
+use crate::expr::{Expr, Variable};
+use crate::state::State;
+use std::rc::Rc;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ProofTree {
+    pub rule: String,
+    pub conclusion: Expr,
+    pub premises: Vec<ProofTree>,
+}
+
+impl Proof {
+    pub fn to_tree(&self) -> ProofTree {
+        self.reify_tree(&self.state, &mut Vec::new())
+    }
+
+    pub(crate) fn reify_tree(
+        &self,
+        state: &State,
+        names: &mut Vec<(usize, Rc<Variable>)>,
+    ) -> ProofTree {
+        let (scope, expr) = &self.conclusion;
+        let conclusion = state.rename((*scope, expr), names);
+
+        let premises = self.parents.iter();
+        let premises = premises.map(|parent| parent.reify_tree(state, names));
+
+        ProofTree {
+            rule: self.rule.clone(),
+            conclusion,
+            premises: premises.collect(),
+        }
+    }
+
+    pub(crate) fn from_tree(tree: &ProofTree, state: &State, scope: usize) -> Proof {
+        let premises = tree.premises.iter();
+        let parents = premises.map(|premise| Rc::new(Proof::from_tree(premise, state, scope)));
+
+        Proof::new(
+            &tree.rule,
+            state,
+            parents.collect(),
+            (scope, &tree.conclusion),
+        )
+    }
+}
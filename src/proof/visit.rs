@@ -0,0 +1,20 @@
+use super::Proof;
+
+pub trait Visitor {
+    fn enter(&mut self, _proof: &Proof) -> bool {
+        true
+    }
+
+    fn leave(&mut self, _proof: &Proof) {}
+}
+
+impl Proof {
+    pub fn visit<V: Visitor + ?Sized>(&self, visitor: &mut V) {
+        if visitor.enter(self) {
+            for parent in &self.parents {
+                parent.visit(visitor);
+            }
+        }
+        visitor.leave(self);
+    }
+}
@@ -2,11 +2,28 @@ mod tests;
 
 use crate::expr::*;
 use im::hashmap::HashMap;
+use im::vector::Vector;
 use std::rc::Rc;
 
-#[derive(Clone, Default)]
+type Scoped = (usize, Expr);
+
+#[derive(Clone)]
 pub struct State {
-    values: HashMap<(usize, Rc<Variable>), (usize, Expr)>,
+    values: HashMap<(usize, Rc<Variable>), Scoped>,
+    constraints: Vector<(Scoped, Scoped)>,
+    scopes: usize,
+    occurs_check: bool,
+}
+
+impl Default for State {
+    fn default() -> State {
+        State {
+            values: HashMap::new(),
+            constraints: Vector::new(),
+            scopes: 0,
+            occurs_check: true,
+        }
+    }
 }
 
 impl State {
@@ -14,8 +31,13 @@ impl State {
         State::default()
     }
 
-    pub fn scope(&self) -> usize {
-        self.values.len() + 1
+    pub fn set_occurs_check(&mut self, enabled: bool) {
+        self.occurs_check = enabled;
+    }
+
+    pub fn new_scope(&mut self) -> usize {
+        self.scopes += 1;
+        self.scopes
     }
 
     pub fn resolve(&self, expr: &Expr) -> Expr {
@@ -53,19 +75,73 @@ impl State {
     pub fn unify(&self, x: (usize, &Expr), y: (usize, &Expr)) -> Option<State> {
         let mut state = self.clone();
 
-        if state.unify_mut(x, y) {
+        if state.unify_mut(x, y) && state.check_constraints() {
+            Some(state)
+        } else {
+            None
+        }
+    }
+
+    pub fn matches(&self, pattern: (usize, &Expr), target: (usize, &Expr)) -> Option<State> {
+        let mut free = Vec::new();
+        self.free_variables(target, &mut free);
+
+        let state = self.unify(pattern, target)?;
+
+        let unbound = free.iter().all(|(scope, var)| {
+            let expr = Expr::Var(var.clone());
+            match state.resolve_var((*scope, &expr)) {
+                (s, Expr::Var(v)) => s == *scope && v == *var,
+                _ => false,
+            }
+        });
+
+        if unbound {
+            Some(state)
+        } else {
+            None
+        }
+    }
+
+    pub fn disunify(&self, x: (usize, &Expr), y: (usize, &Expr)) -> Option<State> {
+        let mut state = self.clone();
+        let constraint = ((x.0, x.1.clone()), (y.0, y.1.clone()));
+        state.constraints.push_back(constraint);
+
+        if state.check_constraints() {
             Some(state)
         } else {
             None
         }
     }
 
+    fn check_constraints(&mut self) -> bool {
+        let mut pending = Vector::new();
+
+        for (x, y) in self.constraints.iter() {
+            let mut state = self.clone();
+
+            if !state.unify_mut((x.0, &x.1), (y.0, &y.1)) {
+                continue;
+            }
+            if state.values.len() == self.values.len() {
+                return false;
+            }
+            pending.push_back((x.clone(), y.clone()));
+        }
+
+        self.constraints = pending;
+        true
+    }
+
     fn unify_mut(&mut self, x: (usize, &Expr), y: (usize, &Expr)) -> bool {
         let (x_scope, x) = self.resolve_var(x);
         let (y_scope, y) = self.resolve_var(y);
 
         match (&x, &y) {
             (Expr::Wrd(a), Expr::Wrd(b)) => a == b,
+            (Expr::Num(a), Expr::Num(b)) => a == b,
+            (Expr::Str(a), Expr::Str(b)) => a == b,
             (Expr::Var(a), Expr::Var(b)) if a == b && x_scope == y_scope => true,
             (Expr::Var(v), _) => self.assign((x_scope, v), (y_scope, y)),
             (_, Expr::Var(v)) => self.assign((y_scope, v), (x_scope, x)),
@@ -107,11 +183,119 @@ impl State {
     }
 
     fn assign(&mut self, var: (usize, &Rc<Variable>), expr: (usize, Expr)) -> bool {
+        if self.occurs_check && self.occurs(var, (expr.0, &expr.1)) {
+            return false;
+        }
+
         self.values.insert((var.0, var.1.clone()), expr);
         true
     }
 
-    fn resolve_var(&self, expr: (usize, &Expr)) -> (usize, Expr) {
+    fn occurs(&self, var: (usize, &Rc<Variable>), expr: (usize, &Expr)) -> bool {
+        let (scope, expr) = self.resolve_var(expr);
+
+        match &expr {
+            Expr::Var(v) => scope == var.0 && v == var.1,
+            Expr::Seq(seq) => seq.0.iter().any(|item| self.occurs(var, (scope, item))),
+            Expr::Lst(lst) => lst.pair.as_ref().map_or(false, |Pair { head, tail }| {
+                self.occurs(var, (scope, head)) || self.occurs(var, (scope, tail))
+            }),
+            _ => false,
+        }
+    }
+
+    fn free_variables(&self, expr: (usize, &Expr), vars: &mut Vec<(usize, Rc<Variable>)>) {
+        let (scope, expr) = self.resolve_var(expr);
+
+        match &expr {
+            Expr::Var(var) => {
+                let key = (scope, var.clone());
+                if !vars.contains(&key) {
+                    vars.push(key);
+                }
+            }
+            Expr::Seq(seq) => {
+                for item in &seq.0 {
+                    self.free_variables((scope, item), vars);
+                }
+            }
+            Expr::Lst(lst) => {
+                if let Some(Pair { head, tail }) = &lst.pair {
+                    self.free_variables((scope, head), vars);
+                    self.free_variables((scope, tail), vars);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn constraints(&self) -> impl Iterator<Item = &(Scoped, Scoped)> {
+        self.constraints.iter()
+    }
+
+    pub(crate) fn bindings(&self, scope: usize) -> Vec<(Rc<Variable>, Expr)> {
+        let vars = self.values.keys().filter(|(s, _)| *s == scope);
+
+        let mut bindings: Vec<_> = vars
+            .map(|(_, var)| {
+                let expr = Expr::Var(var.clone());
+                (var.clone(), self.resolve_scoped(&expr, scope))
+            })
+            .collect();
+
+        bindings.sort_by(|(a, _), (b, _)| a.0.cmp(&b.0));
+        bindings
+    }
+
+    pub fn reify(&self, expr: &Expr) -> Expr {
+        self.reify_scoped(expr, 0)
+    }
+
+    pub fn reify_scoped(&self, expr: &Expr, scope: usize) -> Expr {
+        self.rename((scope, expr), &mut Vec::new())
+    }
+
+    pub(crate) fn rename(
+        &self,
+        expr: (usize, &Expr),
+        names: &mut Vec<(usize, Rc<Variable>)>,
+    ) -> Expr {
+        let (scope, expr) = self.resolve_var(expr);
+
+        match &expr {
+            Expr::Var(var) => {
+                let key = (scope, var.clone());
+
+                let index = match names.iter().position(|name| *name == key) {
+                    Some(index) => index,
+                    None => {
+                        names.push(key);
+                        names.len() - 1
+                    }
+                };
+
+                Expr::Var(Rc::new(Variable(format!("_{}", index))))
+            }
+            Expr::Seq(seq) => {
+                let items = seq.0.iter().map(|item| self.rename((scope, item), names));
+                Expr::Seq(Rc::new(Sequence(items.collect())))
+            }
+            Expr::Lst(lst) => {
+                let pair = lst.pair.as_ref().map(|Pair { head, tail }| Pair {
+                    head: self.rename((scope, head), names),
+                    tail: self.rename((scope, tail), names),
+                });
+
+                Expr::Lst(Rc::new(List {
+                    tag: lst.tag.clone(),
+                    pair,
+                }))
+            }
+            _ => expr,
+        }
+    }
+
+    pub(crate) fn resolve_var(&self, expr: (usize, &Expr)) -> (usize, Expr) {
         let mut expr = expr;
 
         while let Expr::Var(var) = expr.1 {
@@ -125,3 +309,9 @@ impl State {
         (expr.0, expr.1.clone())
     }
 }
+
+impl Expr {
+    pub fn subsumes(&self, other: &Expr) -> bool {
+        State::new().matches((0, self), (1, other)).is_some()
+    }
+}
@@ -267,3 +267,232 @@ fn unify_list_with_tail_variable() {
         expr!(lst(k, [wrd(c), wrd(d),]))
     );
 }
+
+#[test]
+fn reject_variable_inside_own_value() {
+    let state = unify!(var(x), seq(wrd(f), var(x)));
+    assert!(state.is_none());
+}
+
+#[test]
+fn reject_variable_inside_own_list_tail() {
+    let state = unify!(var(x), lst(k, [wrd(a) | var(x)]));
+    assert!(state.is_none());
+}
+
+#[test]
+fn reject_indirect_cycle() {
+    let state = unify!(seq(var(x), var(y)), seq(var(y), seq(wrd(f), var(x))));
+    assert!(state.is_none());
+}
+
+#[test]
+fn reject_indirect_cycle_through_nested_values() {
+    let state = unify!(
+        seq(var(x), var(y)),
+        seq(seq(wrd(f), var(y)), lst(k, [wrd(a), var(x),]))
+    );
+    assert!(state.is_none());
+}
+
+#[test]
+fn reject_cycle_across_scopes() {
+    let x = expr!(var(x));
+    let y = expr!(var(y));
+    let f_x = expr!(seq(wrd(f), var(x)));
+
+    let state = State::new().unify((0, &x), (1, &y)).unwrap();
+    assert!(state.unify((1, &y), (0, &f_x)).is_none());
+}
+
+#[test]
+fn allow_same_name_in_different_scope() {
+    let x = expr!(var(x));
+    let f_x = expr!(seq(wrd(f), var(x)));
+
+    let state = State::new().unify((0, &x), (1, &f_x)).unwrap();
+    assert_eq!(state.resolve(&x), f_x);
+}
+
+#[test]
+fn allow_cycles_without_occurs_check() {
+    let mut state = State::new();
+    state.set_occurs_check(false);
+
+    let state = state.unify((0, &expr!(var(x))), (0, &expr!(seq(wrd(f), var(x)))));
+    assert_eq!(state.unwrap().values.len(), 1);
+}
+
+#[test]
+fn allocate_distinct_scopes_without_bindings() {
+    let mut state = State::new();
+    assert_eq!(state.new_scope(), 1);
+    assert_eq!(state.new_scope(), 2);
+
+    let mut state = state
+        .unify((0, &expr!(wrd(a))), (0, &expr!(wrd(a))))
+        .unwrap();
+    assert_eq!(state.new_scope(), 3);
+}
+
+#[test]
+fn allocate_scopes_independently_in_cloned_states() {
+    let mut state = State::new();
+    state.new_scope();
+
+    let mut copy = state.clone();
+    assert_eq!(copy.new_scope(), 2);
+    assert_eq!(state.new_scope(), 2);
+}
+
+#[test]
+fn reject_disequality_of_equal_words() {
+    let state = State::new().disunify((0, &expr!(wrd(a))), (0, &expr!(wrd(a))));
+    assert!(state.is_none());
+}
+
+#[test]
+fn accept_disequality_of_different_words() {
+    let state = State::new().disunify((0, &expr!(wrd(a))), (0, &expr!(wrd(b))));
+    assert!(state.unwrap().constraints.is_empty());
+}
+
+#[test]
+fn reject_binding_that_violates_disequality() {
+    let x = expr!(var(x));
+    let state = State::new().disunify((0, &x), (0, &expr!(wrd(a)))).unwrap();
+
+    assert!(state.unify((0, &x), (0, &expr!(wrd(a)))).is_none());
+    assert!(state.unify((0, &x), (0, &expr!(wrd(b)))).is_some());
+}
+
+#[test]
+fn reject_aliasing_variables_that_must_differ() {
+    let (x, y) = (expr!(var(x)), expr!(var(y)));
+    let state = State::new().disunify((0, &x), (1, &y)).unwrap();
+
+    assert!(state.unify((0, &x), (1, &y)).is_none());
+    assert!(state.unify((0, &x), (0, &y)).is_some());
+}
+
+#[test]
+fn keep_disequality_until_decided() {
+    let pair = expr!(seq(var(x), var(y)));
+    let state = State::new()
+        .disunify((0, &pair), (0, &expr!(seq(wrd(a), wrd(b)))))
+        .unwrap();
+
+    let state = state
+        .unify((0, &expr!(var(x))), (0, &expr!(wrd(a))))
+        .unwrap();
+    assert_eq!(state.constraints.len(), 1);
+
+    assert!(state
+        .unify((0, &expr!(var(y))), (0, &expr!(wrd(b))))
+        .is_none());
+
+    let state = state
+        .unify((0, &expr!(var(y))), (0, &expr!(wrd(c))))
+        .unwrap();
+    assert!(state.constraints.is_empty());
+}
+
+#[test]
+fn unify_equal_numbers() {
+    assert!(unify!(num(3), num(3)).is_some());
+    assert!(unify!(num(3), num(4)).is_none());
+    assert!(unify!(num(3), wrd(3)).is_none());
+}
+
+#[test]
+fn unify_equal_strings() {
+    assert!(unify!(str("a b"), str("a b")).is_some());
+    assert!(unify!(str("a"), str("b")).is_none());
+    assert!(unify!(str("a"), wrd(a)).is_none());
+}
+
+#[test]
+fn reify_free_variables_canonically() {
+    let mut state = State::new();
+    let scope = state.new_scope();
+
+    let state = state
+        .unify(
+            (0, &expr!(seq(var(x), var(y), var(z)))),
+            (scope, &expr!(seq(var(tail), wrd(a), var(x)))),
+        )
+        .unwrap();
+
+    assert_eq!(
+        state.reify(&expr!(seq(var(x), var(y), var(z), var(x)))),
+        expr!(seq(var(_0), wrd(a), var(_1), var(_0)))
+    );
+}
+
+#[test]
+fn keep_same_named_variables_from_different_scopes_distinct() {
+    let mut state = State::new();
+    let (a, b) = (state.new_scope(), state.new_scope());
+
+    let state = state
+        .unify((0, &expr!(var(x))), (a, &expr!(var(tail))))
+        .unwrap()
+        .unify((0, &expr!(var(y))), (b, &expr!(var(tail))))
+        .unwrap();
+
+    let pair = expr!(seq(var(x), var(y)));
+
+    assert_eq!(state.resolve(&pair), expr!(seq(var(tail), var(tail))));
+    assert_eq!(state.reify(&pair), expr!(seq(var(_0), var(_1))));
+    assert_eq!(state.reify_scoped(&expr!(var(tail)), b), expr!(var(_0)));
+}
+
+fn matches(pattern: &str, target: &str) -> Option<State> {
+    let (pattern, target) = (parse(pattern).unwrap(), parse(target).unwrap());
+    State::new().matches((0, &pattern), (1, &target))
+}
+
+#[test]
+fn match_pattern_variables_against_target() {
+    let state = matches("add $x (s $y)", "add z (s (s $n))").unwrap();
+
+    assert_eq!(state.resolve(&expr!(var(x))), expr!(wrd(z)));
+    assert_eq!(state.reify(&expr!(var(y))), expr!(seq(wrd(s), var(_0))));
+}
+
+#[test]
+fn reject_matches_that_bind_target_variables() {
+    assert!(matches("f a", "f $x").is_none());
+    assert!(matches("f $x $x", "f $a $b").is_none());
+    assert!(matches("f $x $x", "f a b").is_none());
+
+    assert!(matches("f $x $x", "f $a $a").is_some());
+    assert!(matches("f $x $y", "f $a $a").is_some());
+}
+
+#[test]
+fn match_lists_with_tail_variables() {
+    let state = matches("k[$h | $t]", "k[a, b | $rest]").unwrap();
+    assert_eq!(state.reify(&expr!(var(t))), parse("k[b | $_0]").unwrap());
+
+    assert!(matches("k[a, b | $t]", "k[a | $rest]").is_none());
+    assert!(matches("k[$h | $t]", "j[a]").is_none());
+    assert!(matches("k[$x, $x]", "k[a, a]").is_some());
+}
+
+#[test]
+fn check_subsumption_between_expressions() {
+    let general = parse("path $x $y").unwrap();
+    let specific = parse("path a $y").unwrap();
+
+    assert!(general.subsumes(&specific));
+    assert!(!specific.subsumes(&general));
+    assert!(general.subsumes(&general));
+
+    assert!(parse("λ[$x | $t]")
+        .unwrap()
+        .subsumes(&parse("λ[a, b]").unwrap()));
+    assert!(!parse("($x $x)")
+        .unwrap()
+        .subsumes(&parse("($y $z)").unwrap()));
+}